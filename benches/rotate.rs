@@ -0,0 +1,89 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use nalgebra::{Matrix3, Matrix3x4, Vector3};
+use puzzle_cube::{Cube, Layer, Move, MoveType};
+
+/// A cube stored the way it was before moves only touched the layers they
+/// turn, as a baseline: every cubit is a matrix of its position and its axes,
+/// and a move looks at all of them.
+struct ScanCube {
+    cubits: Vec<Matrix3x4<isize>>,
+    offset: isize,
+    step: isize,
+}
+
+impl ScanCube {
+    fn new(sides: usize) -> Self {
+        let (offset, step) = if sides % 2 == 0 {
+            (sides as isize - 1, 2)
+        } else {
+            (sides as isize / 2, 1)
+        };
+        let coords = || (-offset..=offset).step_by(step as usize);
+        let mut cubits = Vec::new();
+        for x in coords() {
+            for y in coords() {
+                for z in coords() {
+                    if [x, y, z].iter().any(|v| v.abs() == offset) {
+                        let pos = Vector3::new(x, y, z);
+                        cubits.push(Matrix3x4::from_columns(&[
+                            pos,
+                            Vector3::x(),
+                            Vector3::y(),
+                            Vector3::z(),
+                        ]));
+                    }
+                }
+            }
+        }
+        ScanCube {
+            cubits,
+            offset,
+            step,
+        }
+    }
+
+    /// Turn a layer of the right side clockwise, counted from the outside.
+    fn turn_right(&mut self, layer: usize) {
+        let y = self.offset - layer as isize * self.step;
+        let full = -self.offset..=self.offset;
+        let ranges = [full.clone(), y..=y, full];
+        let rot = Matrix3::new(0, 0, -1, 0, 1, 0, 1, 0, 0);
+        for c in self.cubits.iter_mut() {
+            if (0..3).all(|i| ranges[i].contains(&c[(i, 0)])) {
+                *c = rot * *c;
+            }
+        }
+    }
+}
+
+/// Turn the outer right side and the middle slice next to it, a move only has
+/// to touch the cubits in the layers it turns. The `scan` benchmarks turn the
+/// same layers by looking at every cubit.
+fn rotate(c: &mut Criterion) {
+    let mut group = c.benchmark_group("rotate");
+    group.sample_size(10);
+    for &sides in [3, 10, 100, 1000].iter() {
+        let mut cube = Cube::with_number_sides(sides).unwrap();
+        let outer = Move::rotate_right(Layer::Single(0), MoveType::Clockwise);
+        group.bench_function(BenchmarkId::new("outer", sides), |b| {
+            b.iter(|| cube.rotate(&outer).unwrap())
+        });
+        let slice = Move::rotate_right(Layer::Single(sides / 2), MoveType::Clockwise);
+        group.bench_function(BenchmarkId::new("slice", sides), |b| {
+            b.iter(|| cube.rotate(&slice).unwrap())
+        });
+        drop(cube);
+
+        let mut scan = ScanCube::new(sides);
+        group.bench_function(BenchmarkId::new("scan/outer", sides), |b| {
+            b.iter(|| scan.turn_right(0))
+        });
+        group.bench_function(BenchmarkId::new("scan/slice", sides), |b| {
+            b.iter(|| scan.turn_right(sides / 2))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, rotate);
+criterion_main!(benches);
@@ -0,0 +1,658 @@
+use std::{
+    fmt,
+    iter::FromIterator,
+    ops::{Add, Mul, Range},
+    slice,
+    str::FromStr,
+    vec,
+};
+
+use crate::{
+    error::Error,
+    movement::{
+        format_moves, parse_moves, Axis, AxisInner, Layer, LayerInner, Move, MoveType, Notation,
+        Plane,
+    },
+};
+
+/// An ordered sequence of moves.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Algorithm {
+    moves: Vec<Move>,
+}
+
+impl Algorithm {
+    /// Create an algorithm without any moves.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The moves of the algorithm in the order they are done.
+    pub fn moves(&self) -> &[Move] {
+        &self.moves
+    }
+
+    /// The number of moves in the algorithm.
+    pub fn len(&self) -> usize {
+        self.moves.len()
+    }
+
+    /// Whether the algorithm has no moves.
+    pub fn is_empty(&self) -> bool {
+        self.moves.is_empty()
+    }
+
+    /// Iterate over the moves of the algorithm.
+    pub fn iter(&self) -> slice::Iter<'_, Move> {
+        self.moves.iter()
+    }
+
+    /// Add a move to the end of the algorithm.
+    pub fn push(&mut self, mv: Move) {
+        self.moves.push(mv);
+    }
+
+    /// The algorithm that undoes this one.
+    ///
+    /// The moves are done in reverse order, each turning the opposite direction.
+    pub fn inverse(&self) -> Self {
+        self.moves.iter().rev().map(Move::inverse).collect()
+    }
+
+    /// The algorithm as it would be done in a mirror placed on the given plane.
+    pub fn mirror(&self, plane: Plane) -> Self {
+        self.moves.iter().map(|mv| mv.mirror(plane)).collect()
+    }
+
+    /// The algorithm done `n` times in a row.
+    pub fn repeat(&self, n: usize) -> Self {
+        Algorithm {
+            moves: self.moves.repeat(n),
+        }
+    }
+
+    /// The shortest algorithm doing the same thing as this one on a cube with
+    /// the given number of sides.
+    ///
+    /// Neighbouring moves around the same axis commute, so each run of them is
+    /// merged into the number of quarter turns every layer makes. Turns that
+    /// cancel out are dropped and what remains is written with as few moves as
+    /// possible, preferring face turns over wide turns, slices and rotations.
+    /// Moves on opposite faces are compared by the layer they turn, so on a
+    /// 3x3x3 `L` and `3R` cancel each other out.
+    pub fn simplify(&self, sides: usize) -> Result<Algorithm, Error> {
+        let mut runs: Vec<(Axis, Vec<u8>)> = Vec::new();
+        for mv in self {
+            let (axis, layers, turns) = layer_turns(mv, sides)?;
+            // A run that cancelled out lets the runs on either side of it merge.
+            while matches!(runs.last(), Some((_, t)) if t.iter().all(|&q| q == 0)) {
+                runs.pop();
+            }
+            match runs.last_mut() {
+                Some((a, t)) if *a == axis => {
+                    for q in &mut t[layers] {
+                        *q = (*q + turns) % 4;
+                    }
+                }
+                _ => {
+                    let mut t = vec![0; sides];
+                    for q in &mut t[layers] {
+                        *q = turns;
+                    }
+                    runs.push((axis, t));
+                }
+            }
+        }
+        Ok(runs
+            .iter()
+            .flat_map(|(axis, turns)| layer_moves(*axis, turns))
+            .collect())
+    }
+
+    /// Display the algorithm using the given notation dialect.
+    pub fn notation(&self, notation: Notation) -> String {
+        format_moves(&self.moves, notation)
+    }
+}
+
+impl From<Vec<Move>> for Algorithm {
+    fn from(moves: Vec<Move>) -> Self {
+        Algorithm { moves }
+    }
+}
+
+impl From<Algorithm> for Vec<Move> {
+    fn from(alg: Algorithm) -> Self {
+        alg.moves
+    }
+}
+
+impl FromIterator<Move> for Algorithm {
+    fn from_iter<T: IntoIterator<Item = Move>>(iter: T) -> Self {
+        Algorithm {
+            moves: iter.into_iter().collect(),
+        }
+    }
+}
+
+impl Extend<Move> for Algorithm {
+    fn extend<T: IntoIterator<Item = Move>>(&mut self, iter: T) {
+        self.moves.extend(iter);
+    }
+}
+
+impl IntoIterator for Algorithm {
+    type Item = Move;
+    type IntoIter = vec::IntoIter<Move>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.moves.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a Algorithm {
+    type Item = &'a Move;
+    type IntoIter = slice::Iter<'a, Move>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.moves.iter()
+    }
+}
+
+impl FromStr for Algorithm {
+    type Err = Error;
+
+    /// Parse an algorithm written in WCA or SiGN notation, see [`parse_moves`].
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_moves(s).map(Algorithm::from)
+    }
+}
+
+impl fmt::Display for Algorithm {
+    /// Write the algorithm in WCA notation.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.notation(Notation::Wca))
+    }
+}
+
+impl Add for Algorithm {
+    type Output = Algorithm;
+
+    fn add(mut self, rhs: Algorithm) -> Self::Output {
+        self.moves.extend(rhs.moves);
+        self
+    }
+}
+
+impl Add<&Algorithm> for Algorithm {
+    type Output = Algorithm;
+
+    fn add(mut self, rhs: &Algorithm) -> Self::Output {
+        self.moves.extend_from_slice(&rhs.moves);
+        self
+    }
+}
+
+impl Mul<usize> for Algorithm {
+    type Output = Algorithm;
+
+    fn mul(self, rhs: usize) -> Self::Output {
+        self.repeat(rhs)
+    }
+}
+
+impl Mul<usize> for &Algorithm {
+    type Output = Algorithm;
+
+    fn mul(self, rhs: usize) -> Self::Output {
+        self.repeat(rhs)
+    }
+}
+
+/// The unsigned axis a move turns around, the layers it turns counted from the
+/// positive side and how many quarter turns they make around the positive axis.
+pub(crate) fn layer_turns(mv: &Move, sides: usize) -> Result<(Axis, Range<usize>, u8), Error> {
+    let layers = match mv.affected_range.for_sides(sides)? {
+        LayerInner::Single(l) if l < sides => l..l + 1,
+        LayerInner::Multiple(l) if l <= sides => 0..l,
+        LayerInner::WholeCube => 0..sides,
+        _ => return Err(Error::InvalidMoveLayer),
+    };
+    let turns = mv.move_type.quarter_turns();
+    if mv.axis.is_negative() {
+        let layers = sides - layers.end..sides - layers.start;
+        Ok((mv.axis.unsigned(), layers, 4 - turns))
+    } else {
+        Ok((mv.axis.unsigned(), layers, turns))
+    }
+}
+
+/// A move as `(start, end, turns)`, turning layers `start..end` by `turns`
+/// quarter turns around the positive axis.
+///
+/// Every move changes the difference in turns between neighbouring layers at
+/// exactly two boundaries, the ends of the range. Boundary `0` is before the
+/// first layer and boundary `sides` after the last, allowed moves are those
+/// between neighbouring boundaries (a single layer) or starting or ending at an
+/// outer boundary (turns from a face or the whole cube).
+type Span = (usize, usize, u8);
+
+/// Subsets of boundaries with differences are only searched exhaustively up to this size.
+const MAX_SEARCHED_BOUNDARIES: usize = 12;
+
+/// Weight of a move when comparing ways to write the same turns, large enough
+/// that fewer moves are always preferred over the kind of moves used.
+const MOVE_COST: usize = 1 << 16;
+
+/// The fewest moves turning each layer around the axis by the given quarter turns.
+fn layer_moves(axis: Axis, turns: &[u8]) -> Vec<Move> {
+    let n = turns.len();
+    let diffs: Vec<u8> = (0..=n)
+        .map(|j| {
+            let cur = turns.get(j).copied().unwrap_or(0);
+            let prev = if j == 0 { 0 } else { turns[j - 1] };
+            (cur + 4 - prev) % 4
+        })
+        .collect();
+    let nodes: Vec<usize> = (0..=n).filter(|&j| diffs[j] != 0).collect();
+
+    // Boundaries whose differences sum to zero can be fixed together, using one
+    // move less than there are boundaries if they can be connected directly.
+    // Everything else is connected through an outer boundary.
+    let mut spans = if nodes.len() > MAX_SEARCHED_BOUNDARIES {
+        let hub = if nodes.contains(&n) { n } else { 0 };
+        star(hub, &nodes, &diffs)
+    } else {
+        let groups: Vec<Option<(usize, Vec<Span>)>> = (0..1usize << nodes.len())
+            .map(|mask| connect(&subset(&nodes, mask), &diffs))
+            .collect();
+        let (_, spans) = [0, n]
+            .iter()
+            .map(|&hub| best_partition(hub, &nodes, &diffs, &groups))
+            .min_by_key(|(cost, _)| *cost)
+            .expect("there are two outer boundaries");
+        spans
+    };
+    spans.sort_unstable();
+    spans
+        .into_iter()
+        .filter_map(|span| span_move(axis, n, span))
+        .collect()
+}
+
+fn subset(nodes: &[usize], mask: usize) -> Vec<usize> {
+    nodes
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| mask & (1 << i) != 0)
+        .map(|(_, &j)| j)
+        .collect()
+}
+
+/// Split the boundaries into groups that are connected directly, the rest is
+/// connected through `hub`. Returns the cost and moves of the cheapest split.
+fn best_partition(
+    hub: usize,
+    nodes: &[usize],
+    diffs: &[u8],
+    groups: &[Option<(usize, Vec<Span>)>],
+) -> (usize, Vec<Span>) {
+    let full = (1usize << nodes.len()) - 1;
+    let mut best: Vec<(usize, usize)> = vec![(0, 0); full + 1];
+    for mask in 1..=full {
+        let low = mask & mask.wrapping_neg();
+        let node = nodes[low.trailing_zeros() as usize];
+        let alone = if node == hub {
+            0
+        } else {
+            MOVE_COST + cost(&star(hub, &[node], diffs), diffs.len() - 1)
+        };
+        best[mask] = (best[mask ^ low].0 + alone, low);
+        let rest = mask ^ low;
+        let mut sub = rest;
+        loop {
+            let group = sub | low;
+            if let Some((c, _)) = &groups[group] {
+                let total = best[mask ^ group].0 + c;
+                if total < best[mask].0 {
+                    best[mask] = (total, group);
+                }
+            }
+            if sub == 0 {
+                break;
+            }
+            sub = (sub - 1) & rest;
+        }
+    }
+
+    let mut spans = Vec::new();
+    let mut mask = full;
+    while mask != 0 {
+        let (_, group) = best[mask];
+        match &groups[group] {
+            Some((_, s)) if group.count_ones() > 1 => spans.extend_from_slice(s),
+            _ => spans.extend(star(hub, &subset(nodes, group), diffs)),
+        }
+        mask ^= group;
+    }
+    (best[full].0, spans)
+}
+
+/// The cheapest way to connect a group of boundaries directly, if the group can
+/// be fixed on its own.
+fn connect(group: &[usize], diffs: &[u8]) -> Option<(usize, Vec<Span>)> {
+    let n = diffs.len() - 1;
+    if group.is_empty() || group.iter().map(|&j| diffs[j] as usize).sum::<usize>() % 4 != 0 {
+        return None;
+    }
+    let mut trees = Vec::new();
+    if group.contains(&0) {
+        trees.push(group.iter().filter(|&&j| j != 0).map(|&j| (0, j)).collect());
+    }
+    if group.contains(&n) {
+        trees.push(group.iter().filter(|&&j| j != n).map(|&j| (j, n)).collect());
+    }
+    if group.contains(&0) && group.contains(&n) {
+        // Every other boundary hangs off one of the outer boundaries, which are
+        // joined directly or through one of the others.
+        let inner: Vec<usize> = group
+            .iter()
+            .copied()
+            .filter(|&j| j != 0 && j != n)
+            .collect();
+        for sides in 0..1usize << inner.len() {
+            let hang = |i: usize, j: usize| {
+                if sides & (1 << i) == 0 {
+                    (0, j)
+                } else {
+                    (j, n)
+                }
+            };
+            let tree: Vec<(usize, usize)> =
+                inner.iter().enumerate().map(|(i, &j)| hang(i, j)).collect();
+            let mut direct = tree.clone();
+            direct.push((0, n));
+            trees.push(direct);
+            for (i, &j) in inner.iter().enumerate() {
+                let mut via = tree.clone();
+                via.push(if hang(i, j) == (0, j) { (j, n) } else { (0, j) });
+                trees.push(via);
+            }
+        }
+    }
+    if group.windows(2).all(|w| w[0] + 1 == w[1]) {
+        trees.push(group.windows(2).map(|w| (w[0], w[1])).collect());
+    }
+    trees
+        .iter()
+        .map(|tree| {
+            let spans: Vec<Span> = tree_spans(group, tree, diffs)
+                .into_iter()
+                .filter(|&(_, _, turns)| turns != 0)
+                .collect();
+            (spans.len() * MOVE_COST + cost(&spans, n), spans)
+        })
+        .min_by_key(|(c, _)| *c)
+}
+
+/// Turns needed on every edge of a tree connecting the group so each boundary
+/// ends up with its difference.
+fn tree_spans(group: &[usize], tree: &[(usize, usize)], diffs: &[u8]) -> Vec<Span> {
+    // Walk the tree from the first boundary, every edge makes up for the
+    // differences of everything on the far side of it.
+    let mut order = vec![(group[0], None)];
+    let mut seen = vec![group[0]];
+    let mut i = 0;
+    while i < order.len() {
+        let (node, _) = order[i];
+        for (e, &(a, b)) in tree.iter().enumerate() {
+            let next = if a == node {
+                b
+            } else if b == node {
+                a
+            } else {
+                continue;
+            };
+            if !seen.contains(&next) {
+                seen.push(next);
+                order.push((next, Some(e)));
+            }
+        }
+        i += 1;
+    }
+    let mut below: Vec<u8> = order.iter().map(|&(node, _)| diffs[node]).collect();
+    let mut spans = Vec::with_capacity(tree.len());
+    for i in (1..order.len()).rev() {
+        let (node, edge) = order[i];
+        let (a, b) = tree[edge.expect("only the first boundary has no edge")];
+        let turns = if node == a {
+            below[i]
+        } else {
+            (4 - below[i]) % 4
+        };
+        spans.push((a, b, turns));
+        let parent = order
+            .iter()
+            .position(|&(p, _)| p == if node == a { b } else { a })
+            .expect("parent was visited first");
+        below[parent] = (below[parent] + below[i]) % 4;
+    }
+    spans
+}
+
+/// Connect every boundary of the group to an outer boundary.
+fn star(hub: usize, group: &[usize], diffs: &[u8]) -> Vec<Span> {
+    group
+        .iter()
+        .filter(|&&j| j != hub)
+        .map(|&j| {
+            if hub < j {
+                (hub, j, (4 - diffs[j]) % 4)
+            } else {
+                (j, hub, diffs[j])
+            }
+        })
+        .collect()
+}
+
+/// Preference between moves of the same length, face turns are best followed
+/// by wide turns and slices with whole cube rotations last.
+fn cost(spans: &[Span], sides: usize) -> usize {
+    spans
+        .iter()
+        .map(|&(start, end, _)| match (start, end) {
+            (0, e) if e == sides => 3,
+            (0, 1) => 0,
+            (s, e) if s + 1 == sides && e == sides => 0,
+            _ => 1,
+        })
+        .sum()
+}
+
+fn span_move(axis: Axis, sides: usize, (start, end, turns): Span) -> Option<Move> {
+    let pos = AxisInner::from(axis);
+    let neg = pos.negate();
+    let (axis, layer, turns) = if start == 0 && end == sides {
+        (pos, LayerInner::WholeCube, turns)
+    } else if start == 0 {
+        (pos, outer(end).into(), turns)
+    } else if end == sides {
+        (neg, outer(sides - start).into(), 4 - turns)
+    } else {
+        let from_neg = sides - 1 - start;
+        if start == from_neg {
+            // The middle layer is written as `S`, `M` or `E`.
+            if pos.is_middle_axis() {
+                (pos, LayerInner::Middle, turns)
+            } else {
+                (neg, LayerInner::Middle, 4 - turns)
+            }
+        } else if start < from_neg {
+            (pos, LayerInner::Single(start), turns)
+        } else {
+            (neg, LayerInner::Single(from_neg), 4 - turns)
+        }
+    };
+    Some(Move {
+        move_type: MoveType::from_quarter_turns(turns)?,
+        axis,
+        affected_range: layer,
+    })
+}
+
+fn outer(layers: usize) -> Layer {
+    if layers == 1 {
+        Layer::Single(0)
+    } else {
+        Layer::Multiple(layers)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn alg(notation: &str) -> Algorithm {
+        notation.parse().unwrap()
+    }
+
+    #[test]
+    fn test_inverse() {
+        assert_eq!(alg("R U R' U2 3Fw x").inverse(), alg("x' 3Fw' U2 R U' R'"));
+        assert_eq!(Algorithm::new().inverse(), Algorithm::new());
+    }
+
+    #[test]
+    fn test_mirror() {
+        assert_eq!(alg("R U R' U'").mirror(Plane::M), alg("L' U' L U"));
+        assert_eq!(alg("R U R' U'").mirror(Plane::E), alg("R' D' R D"));
+        assert_eq!(alg("F R U").mirror(Plane::S), alg("B' R' U'"));
+    }
+
+    #[test]
+    fn test_repeat_and_concat() {
+        let sexy = alg("R U R' U'");
+        assert_eq!(sexy.repeat(2), alg("R U R' U' R U R' U'"));
+        assert_eq!(sexy.repeat(0), Algorithm::new());
+        assert_eq!(&sexy * 1, sexy);
+        assert_eq!(sexy.clone() * 3, sexy.repeat(3));
+        assert_eq!(alg("R U") + alg("F"), alg("R U F"));
+        assert_eq!(alg("R U") + &alg("F"), alg("R U F"));
+        assert_eq!((alg("R") + alg("U")) * 2, alg("R U R U"));
+    }
+
+    #[test]
+    fn test_display() {
+        let a = alg("R U' 3r2 y");
+        assert_eq!(a.to_string(), "R U' 3Rw2 y");
+        assert_eq!(a.notation(Notation::Sign), "R U' 3r2 y");
+        assert_eq!(a.len(), 4);
+        assert_eq!(Vec::from(a.clone()), a.moves().to_vec());
+        assert_eq!(a.iter().copied().collect::<Algorithm>(), a);
+    }
+
+    #[test]
+    fn test_simplify() {
+        for (sides, before, after) in [
+            (3, "R R' U U U", "U'"),
+            (3, "R L R", "R2 L"),
+            (3, "R U U' R'", ""),
+            (3, "R U U' R", "R2"),
+            (3, "R U R' U'", "R U R' U'"),
+            (3, "L R'", "R' L"),
+            (3, "L 3R", ""),
+            (3, "R M' L'", "x"),
+            (3, "x R'", "Lw'"),
+            (3, "M2 M2 E S'", "E S'"),
+            (3, "F S B'", "z"),
+            (2, "R L'", "x"),
+            (4, "Rw R'", "2R"),
+            (4, "Rw Lw", "Rw Lw"),
+            (4, "3Rw L'", "x"),
+            (4, "3Rw 3L", "R 2L'"),
+            (5, "R 2R 3R 4R 5R x'", ""),
+            (5, "U D' E D E", "U E2"),
+        ]
+        .iter()
+        {
+            assert_eq!(
+                alg(before).simplify(*sides).unwrap(),
+                alg(after),
+                "simplifying {:?}",
+                before
+            );
+        }
+    }
+
+    #[test]
+    fn test_simplify_invalid_layer() {
+        assert_eq!(alg("R 4R").simplify(3), Err(Error::InvalidMoveLayer));
+        assert_eq!(alg("4Rw").simplify(3), Err(Error::InvalidMoveLayer));
+    }
+
+    #[test]
+    fn test_simplify_same_cube() {
+        use crate::Cube;
+
+        let scramble = alg("R L R 2U 3D' U2 D Rw 3Lw' x' B F' 2F 2B y z' Fw' 4B 2F");
+        let simple = scramble.simplify(4).unwrap();
+        assert!(simple.len() < scramble.len());
+
+        let mut expected = Cube::with_number_sides(4).unwrap();
+        expected.apply(&scramble).unwrap();
+        let mut cube = Cube::with_number_sides(4).unwrap();
+        cube.apply(&simple).unwrap();
+        assert_eq!(cube, expected);
+    }
+
+    #[test]
+    fn test_simplify_random() {
+        use crate::Cube;
+
+        // Random moves from a small linear congruential generator.
+        let mut seed = 7u64;
+        let mut next = |n: usize| {
+            seed = seed
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            (seed >> 33) as usize % n
+        };
+        let faces: [fn(Layer, MoveType) -> Move; 6] = [
+            Move::rotate_top,
+            Move::rotate_bottom,
+            Move::rotate_left,
+            Move::rotate_right,
+            Move::rotate_front,
+            Move::rotate_back,
+        ];
+        let types = [
+            MoveType::Clockwise,
+            MoveType::CounterClockwise,
+            MoveType::Twice,
+        ];
+        for sides in 2..=6 {
+            for _ in 0..20 {
+                let mut scramble = Algorithm::new();
+                for _ in 0..12 {
+                    let axis = next(3);
+                    let face = faces[axis * 2 + next(2)];
+                    let mv = match next(3) {
+                        0 => face(Layer::Single(next(sides)), types[next(3)]),
+                        1 => face(Layer::Multiple(next(sides) + 1), types[next(3)]),
+                        _ => Move::rotate_cube([Axis::Z, Axis::Y, Axis::X][axis], types[next(3)]),
+                    };
+                    scramble.push(mv);
+                }
+                let simple = scramble.simplify(sides).unwrap();
+                assert!(simple.len() <= scramble.len());
+                assert_eq!(simple.simplify(sides).unwrap(), simple);
+
+                let mut expected = Cube::with_number_sides(sides).unwrap();
+                expected.apply(&scramble).unwrap();
+                let mut cube = Cube::with_number_sides(sides).unwrap();
+                cube.apply(&simple).unwrap();
+                assert_eq!(cube, expected, "{} became {}", scramble, simple);
+            }
+        }
+    }
+}
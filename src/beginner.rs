@@ -0,0 +1,505 @@
+use crate::{
+    cube::Cube,
+    cubie::CubieCube,
+    error::Error,
+    facelet::Color,
+    method::{
+        alg, corner_solved, edge_solved, first_layer, first_two_layers, hold_down, inverse,
+        up_turns, Solving, BOTTOM_CORNERS, CROSS, MIDDLE_EDGES,
+    },
+    movement::Move,
+};
+use std::fmt;
+
+const TWO_CORNERS: &str = "R U R' U'";
+const RIGHT_INSERT: &str = "U R U' R' U' F' U F";
+const LEFT_INSERT: &str = "U' L' U L U F U' F'";
+const FLIP_EDGES: &str = "F R U R' U' F'";
+const TWIST_CORNER: &str = "R' D' R D";
+const CYCLE_CORNERS: &str = "R' F R' B2 R F' R' B2 R2";
+const CYCLE_EDGES: &str = "R U' R U R U R U' R' U' R2";
+
+/// A stage of the beginner's layer by layer method.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BeginnerStage {
+    /// The white edges on the bottom, matching the centers beside them.
+    Cross,
+    /// The white corners, finishing the first layer.
+    FirstLayerCorners,
+    /// The edges of the middle layer.
+    SecondLayer,
+    /// The yellow edges facing up.
+    YellowCross,
+    /// The yellow corners facing up.
+    YellowCornerOrientation,
+    /// The yellow corners in their places.
+    YellowCornerPermutation,
+    /// The yellow edges in their places, solving the cube.
+    YellowEdgePermutation,
+}
+
+impl BeginnerStage {
+    /// Every stage in the order they are solved.
+    pub const ALL: [BeginnerStage; 7] = [
+        BeginnerStage::Cross,
+        BeginnerStage::FirstLayerCorners,
+        BeginnerStage::SecondLayer,
+        BeginnerStage::YellowCross,
+        BeginnerStage::YellowCornerOrientation,
+        BeginnerStage::YellowCornerPermutation,
+        BeginnerStage::YellowEdgePermutation,
+    ];
+
+    /// The name of the stage as shown to a person.
+    pub fn name(self) -> &'static str {
+        match self {
+            BeginnerStage::Cross => "White cross",
+            BeginnerStage::FirstLayerCorners => "First layer corners",
+            BeginnerStage::SecondLayer => "Second layer",
+            BeginnerStage::YellowCross => "Yellow cross",
+            BeginnerStage::YellowCornerOrientation => "Orient yellow corners",
+            BeginnerStage::YellowCornerPermutation => "Permute yellow corners",
+            BeginnerStage::YellowEdgePermutation => "Permute yellow edges",
+        }
+    }
+
+    /// How the stage is solved, with algorithms written for the front side.
+    pub fn explanation(self) -> &'static str {
+        match self {
+            BeginnerStage::Cross => {
+                "Bring the white edges up around the yellow center with their white \
+                 stickers facing up, making a daisy. Then turn the top until the other \
+                 sticker of a white edge matches the center below it and turn that side \
+                 twice to bring the edge down. Repeat for every white edge to make a \
+                 white cross on the bottom."
+            }
+            BeginnerStage::FirstLayerCorners => {
+                "Turn the top until a white corner is above the place it belongs, \
+                 between the centers of its colors. Hold that place at the front right \
+                 and repeat R U R' U' until the corner is solved. A white corner in the \
+                 wrong place on the bottom is brought up with R U R' U' first."
+            }
+            BeginnerStage::SecondLayer => {
+                "Turn the top until an edge without yellow matches the center below it, \
+                 then insert it to the right with U R U' R' U' F' U F or to the left \
+                 with U' L' U L U F U' F', towards the center of its top color. An edge \
+                 in the wrong place in the middle layer is taken out by inserting \
+                 another edge there first."
+            }
+            BeginnerStage::YellowCross => {
+                "Do F R U R' U' F' until the top shows a yellow cross. A dot becomes an \
+                 L, which is held at the back left to become a line, which is held \
+                 across to become the cross."
+            }
+            BeginnerStage::YellowCornerOrientation => {
+                "Hold a corner without yellow on top at the front right and repeat \
+                 R' D' R D until yellow faces up. Turn only the top to bring the next \
+                 such corner to the front right and repeat. The first two layers come \
+                 back together once every yellow corner faces up."
+            }
+            BeginnerStage::YellowCornerPermutation => {
+                "Cycle three yellow corners with R' F R' B2 R F' R' B2 R2, which keeps \
+                 the front left corner where it is, until the top can be turned so the \
+                 corners match the centers. Then turn the top that way."
+            }
+            BeginnerStage::YellowEdgePermutation => {
+                "Cycle three yellow edges with R U' R U R U R U' R' U' R2, which keeps \
+                 the back edge where it is, until every edge matches the center below \
+                 it. Hold a solved edge at the back if there is one."
+            }
+        }
+    }
+}
+
+impl fmt::Display for BeginnerStage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// The moves solving one stage of the beginner's method.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct BeginnerStep {
+    stage: BeginnerStage,
+    moves: Vec<Move>,
+    explanation: String,
+}
+
+impl BeginnerStep {
+    fn new(stage: BeginnerStage, moves: Vec<Move>) -> Self {
+        let explanation = if moves.is_empty() {
+            format!("{} Nothing needs doing this time.", stage.explanation())
+        } else {
+            stage.explanation().to_string()
+        };
+        BeginnerStep {
+            stage,
+            moves,
+            explanation,
+        }
+    }
+
+    /// The stage solved by the moves.
+    pub fn stage(&self) -> BeginnerStage {
+        self.stage
+    }
+
+    /// The moves solving the stage, none if it was already solved.
+    pub fn moves(&self) -> &[Move] {
+        &self.moves
+    }
+
+    /// How the moves solve the stage.
+    pub fn explanation(&self) -> &str {
+        &self.explanation
+    }
+}
+
+/// Finds solutions of a 3x3x3 that a person can follow, using the beginner's
+/// layer by layer method.
+///
+/// The cube is first turned so the white center is at the bottom. The first
+/// two layers are then solved one piece at a time and the last layer with a few
+/// short algorithms, see [`BeginnerStage`]. Algorithms are explained as seen
+/// from the front and made from whichever side they are needed on, so the moves
+/// only turn sides after the first rotation. Solutions are usually 120 to 200
+/// moves long.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct BeginnerSolver;
+
+impl BeginnerSolver {
+    /// Create a beginner's method solver.
+    pub fn new() -> Self {
+        BeginnerSolver
+    }
+
+    /// The moves solving each stage of a 3x3x3, in order.
+    ///
+    /// The whole cube rotation putting the white center at the bottom is part
+    /// of the [`BeginnerStage::Cross`] moves. Returns [`Error::Unsolvable`] if
+    /// the cube can't be solved by turning the sides, and
+    /// [`Error::StepNotFound`] if a stage gets stuck.
+    pub fn solve(&self, cube: &Cube) -> Result<Vec<BeginnerStep>, Error> {
+        let (rotation, state) = hold_down(cube, Color::White)?;
+        let mut solving = Solving::new(state);
+        let mut steps = Vec::with_capacity(BeginnerStage::ALL.len());
+        for &stage in BeginnerStage::ALL.iter() {
+            match stage {
+                BeginnerStage::Cross => cross(&mut solving)?,
+                BeginnerStage::FirstLayerCorners => first_layer_corners(&mut solving)?,
+                BeginnerStage::SecondLayer => second_layer(&mut solving)?,
+                BeginnerStage::YellowCross => yellow_cross(&mut solving)?,
+                BeginnerStage::YellowCornerOrientation => orient_corners(&mut solving)?,
+                BeginnerStage::YellowCornerPermutation => permute_corners(&mut solving)?,
+                BeginnerStage::YellowEdgePermutation => permute_edges(&mut solving)?,
+            }
+            let mut moves = solving.take();
+            if stage == BeginnerStage::Cross {
+                moves.splice(0..0, rotation.iter().copied());
+            }
+            steps.push(BeginnerStep::new(stage, moves));
+        }
+        debug_assert!(solving.cube.is_solved());
+        Ok(steps)
+    }
+}
+
+/// Every face turn of an algorithm from each side, after each turn of the top.
+fn candidates(algs: &[&str]) -> Result<Vec<Vec<usize>>, Error> {
+    let mut candidates = Vec::new();
+    for quarters in 0..4 {
+        for front in 0..4 {
+            for notation in algs {
+                let mut turns = up_turns(quarters);
+                turns.extend(alg(notation, front)?);
+                candidates.push(turns);
+            }
+        }
+    }
+    Ok(candidates)
+}
+
+/// Whether a white edge is in the top layer with its white sticker facing up.
+fn is_petal(cube: &CubieCube, edge: usize) -> bool {
+    let at = cube.ep.iter().position(|&e| e as usize == edge).unwrap();
+    at < 4 && cube.eo[at] == 0
+}
+
+fn cross(solving: &mut Solving) -> Result<(), Error> {
+    let done = |c: &CubieCube, e: usize| edge_solved(c, e) || is_petal(c, e);
+    loop {
+        let before = solving.cube;
+        let count = CROSS.iter().filter(|&&e| done(&before, e)).count();
+        if count == CROSS.len() {
+            break;
+        }
+        let found = solving.search(6, |c| {
+            CROSS.iter().all(|&e| {
+                (!edge_solved(&before, e) || edge_solved(c, e))
+                    && (!is_petal(&before, e) || is_petal(c, e))
+            }) && CROSS.iter().filter(|&&e| done(c, e)).count() > count
+        });
+        if !found {
+            return Err(Error::StepNotFound("add a white edge to the daisy"));
+        }
+    }
+    for (front, &edge) in CROSS.iter().enumerate() {
+        let down: Vec<Vec<usize>> = (0..4)
+            .map(|quarters| {
+                let mut turns = up_turns(quarters);
+                turns.extend(alg("F2", front)?);
+                Ok(turns)
+            })
+            .collect::<Result<_, Error>>()?;
+        let before = solving.cube;
+        let found = solving.reach(&down, 1, |c| {
+            edge_solved(c, edge)
+                && CROSS.iter().all(|&e| {
+                    (!edge_solved(&before, e) || edge_solved(c, e))
+                        && (!is_petal(&before, e) || e == edge || is_petal(c, e))
+                })
+        });
+        if !found {
+            return Err(Error::StepNotFound("turn a petal down"));
+        }
+    }
+    Ok(())
+}
+
+fn first_layer_corners(solving: &mut Solving) -> Result<(), Error> {
+    for (front, &corner) in BOTTOM_CORNERS.iter().enumerate() {
+        let done = |c: &CubieCube| {
+            CROSS.iter().all(|&e| edge_solved(c, e))
+                && BOTTOM_CORNERS[..front].iter().all(|&k| corner_solved(c, k))
+        };
+        let two_corners = alg(TWO_CORNERS, front)?;
+        let insert: Vec<Vec<usize>> = (0..4)
+            .flat_map(|quarters| {
+                let two_corners = &two_corners;
+                (1..=5).map(move |times| {
+                    let mut turns = up_turns(quarters);
+                    turns.extend(two_corners.repeat(times));
+                    turns
+                })
+            })
+            .collect();
+        let take_out = (0..4)
+            .map(|f| alg(TWO_CORNERS, f))
+            .collect::<Result<Vec<_>, _>>()?;
+        while !solving.reach(&insert, 1, |c| done(c) && corner_solved(c, corner)) {
+            let found = solving.reach(&take_out, 1, |c| {
+                done(c) && c.cp.iter().position(|&k| k as usize == corner).unwrap() < 4
+            });
+            if !found {
+                return Err(Error::StepNotFound("bring a white corner up"));
+            }
+        }
+    }
+    Ok(())
+}
+
+fn second_layer(solving: &mut Solving) -> Result<(), Error> {
+    let insert = candidates(&[RIGHT_INSERT, LEFT_INSERT])?;
+    let take_out = (0..4)
+        .map(|f| alg(RIGHT_INSERT, f))
+        .collect::<Result<Vec<_>, _>>()?;
+    for (i, &edge) in MIDDLE_EDGES.iter().enumerate() {
+        let done =
+            |c: &CubieCube| first_layer(c) && MIDDLE_EDGES[..i].iter().all(|&e| edge_solved(c, e));
+        while !solving.reach(&insert, 1, |c| done(c) && edge_solved(c, edge)) {
+            let found = solving.reach(&take_out, 1, |c| {
+                done(c) && c.ep.iter().position(|&e| e as usize == edge).unwrap() < 4
+            });
+            if !found {
+                return Err(Error::StepNotFound("bring a middle layer edge up"));
+            }
+        }
+    }
+    Ok(())
+}
+
+fn yellow_cross(solving: &mut Solving) -> Result<(), Error> {
+    let flip_edges = alg(FLIP_EDGES, 0)?;
+    let flip: Vec<Vec<usize>> = (0..4)
+        .map(|quarters| {
+            let mut turns = up_turns(quarters);
+            turns.extend(&flip_edges);
+            turns
+        })
+        .collect();
+    // A dot needs three, going through an L and a line.
+    let found = solving.reach(&flip, 3, |c| {
+        first_two_layers(c) && c.eo[..4].iter().all(|&o| o == 0)
+    });
+    if !found {
+        return Err(Error::StepNotFound("make the yellow cross"));
+    }
+    Ok(())
+}
+
+fn orient_corners(solving: &mut Solving) -> Result<(), Error> {
+    let twisted = |c: &CubieCube| c.co[..4].iter().any(|&o| o != 0);
+    let twist = alg(TWIST_CORNER, 0)?;
+    while twisted(&solving.cube) {
+        // The piece at the front right may be a white corner part way through.
+        while solving.cube.cp[0] >= 4 || solving.cube.co[0] != 0 {
+            solving.apply(&twist);
+        }
+        if twisted(&solving.cube) {
+            solving.apply(&up_turns(1));
+        }
+    }
+    debug_assert!(first_two_layers(&solving.cube));
+    Ok(())
+}
+
+/// The turn of the top bringing every yellow corner to its place, if any.
+fn corner_alignment(cube: &CubieCube) -> Option<usize> {
+    (0..4).find(|&quarters| {
+        let turned = up_turns(quarters).iter().fold(*cube, |c, &m| c.turn(m));
+        (0..4).all(|k| corner_solved(&turned, k))
+    })
+}
+
+fn permute_corners(solving: &mut Solving) -> Result<(), Error> {
+    let mut cycles = Vec::with_capacity(8);
+    for front in 0..4 {
+        let turns = alg(CYCLE_CORNERS, front)?;
+        cycles.push(inverse(&turns));
+        cycles.push(turns);
+    }
+    let found = solving.reach(&cycles, 2, |c| {
+        first_two_layers(c) && corner_alignment(c).is_some()
+    });
+    let quarters = corner_alignment(&solving.cube)
+        .filter(|_| found)
+        .ok_or(Error::StepNotFound("permute the yellow corners"))?;
+    solving.apply(&up_turns(quarters));
+    Ok(())
+}
+
+fn permute_edges(solving: &mut Solving) -> Result<(), Error> {
+    let mut cycles = Vec::with_capacity(8);
+    for front in 0..4 {
+        let turns = alg(CYCLE_EDGES, front)?;
+        cycles.push(inverse(&turns));
+        cycles.push(turns);
+    }
+    let found = solving.reach(&cycles, 2, CubieCube::is_solved);
+    if !found {
+        return Err(Error::StepNotFound("permute the yellow edges"));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::algorithm::Algorithm;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    fn turned(notation: &str) -> CubieCube {
+        alg(notation, 0)
+            .unwrap()
+            .iter()
+            .fold(CubieCube::SOLVED, |c, &m| c.turn(m))
+    }
+
+    #[test]
+    fn test_algorithms() {
+        let corners = turned(CYCLE_CORNERS);
+        assert!(first_two_layers(&corners));
+        assert!((0..12).all(|e| edge_solved(&corners, e)));
+        assert!(corners.co.iter().all(|&o| o == 0));
+        // The front left corner stays.
+        assert_eq!(
+            (0..4)
+                .filter(|&k| corner_solved(&corners, k))
+                .collect::<Vec<_>>(),
+            vec![1]
+        );
+
+        let edges = turned(CYCLE_EDGES);
+        assert!(first_two_layers(&edges));
+        assert!((0..8).all(|k| corner_solved(&edges, k)));
+        assert!(edges.eo.iter().all(|&o| o == 0));
+        // The back edge stays.
+        assert_eq!(
+            (0..4)
+                .filter(|&e| edge_solved(&edges, e))
+                .collect::<Vec<_>>(),
+            vec![3]
+        );
+
+        assert!(turned(&[TWO_CORNERS; 6].join(" ")).is_solved());
+        assert!(first_two_layers(&turned("F R U R' U' F'")));
+        assert!(turned(&[TWIST_CORNER; 6].join(" ")).is_solved());
+    }
+
+    #[test]
+    fn test_solve() {
+        let solver = BeginnerSolver::new();
+        let steps = solver.solve(&Cube::new3x3x3()).unwrap();
+        assert_eq!(steps.len(), BeginnerStage::ALL.len());
+        assert!(steps.iter().all(|s| s.moves().is_empty()));
+        assert!(steps[0]
+            .explanation()
+            .ends_with("Nothing needs doing this time."));
+
+        let mut rng = ChaCha8Rng::seed_from_u64(16);
+        for _ in 0..20 {
+            let state = CubieCube::random(&mut rng);
+            let mut cube = state.to_cube();
+            let steps = solver.solve(&cube).unwrap();
+            let goals: [fn(&CubieCube) -> bool; 7] = [
+                |c| CROSS.iter().all(|&e| edge_solved(c, e)),
+                first_layer,
+                first_two_layers,
+                |c| first_two_layers(c) && c.eo.iter().all(|&o| o == 0),
+                |c| {
+                    first_two_layers(c)
+                        && c.eo.iter().all(|&o| o == 0)
+                        && c.co.iter().all(|&o| o == 0)
+                },
+                |c| (0..8).all(|k| corner_solved(c, k)) && c.eo.iter().all(|&o| o == 0),
+                CubieCube::is_solved,
+            ];
+            for (step, goal) in steps.iter().zip(goals.iter()) {
+                cube.apply(&Algorithm::from(step.moves().to_vec())).unwrap();
+                assert!(
+                    goal(&CubieCube::from_cube(&cube).unwrap()),
+                    "{}",
+                    step.stage()
+                );
+            }
+            assert!(cube.is_solved());
+        }
+    }
+
+    #[test]
+    fn test_solve_held() {
+        // The cube is turned to put white at the bottom first.
+        let solver = BeginnerSolver::new();
+        for rotation in ["x", "z'", "x2 y", "y'"].iter() {
+            let mut cube = Cube::new3x3x3();
+            cube.apply(&format!("{} R U2 F' L D B2", rotation).parse().unwrap())
+                .unwrap();
+            let steps = solver.solve(&cube).unwrap();
+            let moves: Algorithm = steps.iter().flat_map(|s| s.moves().to_vec()).collect();
+            cube.apply(&moves).unwrap();
+            assert!(cube.is_solved(), "{}", rotation);
+        }
+
+        assert_eq!(
+            solver.solve(&Cube::new2x2x2()),
+            Err(Error::UnsupportedSides {
+                expected: 3,
+                found: 2
+            })
+        );
+        let mut twisted = CubieCube::SOLVED;
+        twisted.co[0] = 1;
+        assert_eq!(solver.solve(&twisted.to_cube()), Err(Error::Unsolvable));
+    }
+}
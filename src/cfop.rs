@@ -0,0 +1,632 @@
+use crate::{
+    algorithm::Algorithm,
+    cube::Cube,
+    cubie::CubieCube,
+    error::Error,
+    facelet::Color,
+    method::{
+        alg, corner_solved, edge_solved, first_two_layers, hold_down, up_turns, Solving,
+        BOTTOM_CORNERS, CROSS, MIDDLE_EDGES,
+    },
+    movement::{LayerInner, Move},
+    optimal::{EdgeMoves, Nibbles},
+};
+use std::{fmt, sync::OnceLock};
+
+/// Inserting the front right pair from each of the 41 places its corner and
+/// edge can be in without being solved.
+const F2L: [&str; 41] = [
+    // The corner and the edge on top.
+    "R U2 R' U' R U R'",
+    "U2 F2 U2 F U F' U F2",
+    "U2 R2 U2 R' U' R U' R2",
+    "F' U2 F U F' U' F",
+    "U2 R U R' U R U' R'",
+    "U' F' U2 F U' F' U F",
+    "U R U2 R' U R U' R'",
+    "U2 F' U' F U' F' U F",
+    "U' R U' R' U R U R'",
+    "R U' R' U2 F' U' F",
+    "U F' U2 F U' R U R'",
+    "U' F' U F",
+    "U' R U R' U R U R'",
+    "U F' U' F U2 F' U F",
+    "R U R'",
+    "U F' U2 F U2 F' U F",
+    "U R U' R'",
+    "F U2 F2 U' F2 U' F'",
+    "F' U F U2 R U R'",
+    "U F' U F U' F' U' F",
+    "U' R U2 R' U2 R U' R'",
+    "F' U' F",
+    "U' R U R' U2 R U' R'",
+    "U F' U' F U' F' U' F",
+    // The corner on top and the edge in its place.
+    "R U R' U' R U R' U' R U R'",
+    "R U' R' F' U2 F",
+    "U R U R' U2 R U R'",
+    "U F' U' F U' R U R'",
+    "U F' U2 F U' F' U' F",
+    "U2 R U R' U2 F' U2 F",
+    // The corner in its place and the edge on top.
+    "U' F' U F U R U' R'",
+    "U R U' R' U' F' U F",
+    "R U' R' U R U' R'",
+    "U2 R' F R F2 U' F",
+    "R U R' U' R U R'",
+    "F' U F U' F' U F",
+    // The corner and the edge in their places.
+    "R2 U2 F R2 F' U2 R' U R'",
+    "R U' R' U' R U R' U2 R U' R'",
+    "R F U R U' R' F' U' R'",
+    "R U' R' U R U2 R' U R U' R'",
+    "R U R' U' R U' R' U2 F' U' F",
+];
+
+/// Orienting the last layer, numbered as usual.
+const OLL: [&str; 57] = [
+    "R U2 R2 F R F' U2 R' F R F'",
+    "F R U R' U' F' f R U R' U' f'",
+    "f R U R' U' f' U' F R U R' U' F'",
+    "f R U R' U' f' U F R U R' U' F'",
+    "l' U2 L U L' U l",
+    "r U2 R' U' R U' r'",
+    "r U R' U R U2 r'",
+    "l' U' L U' L' U2 l",
+    "R U R' U' R' F R2 U R' U' F'",
+    "R U R' U R' F R F' R U2 R'",
+    "r U R' U R' F R F' R U2 r'",
+    "M' R' U' R U' R' U2 R U' R r'",
+    "F U R U' R2 F' R U R U' R'",
+    "R' F R U R' F' R F U' F'",
+    "l' U' l L' U' L U l' U l",
+    "r U r' R U R' U' r U' r'",
+    "F R' F' R2 r' U R U' R' U' M'",
+    "r U R' U R U2 r2 U' R U' R' U2 r",
+    "r' R U R U R' U' M' R' F R F'",
+    "r U R' U' M2 U R U' R' U' M'",
+    "R U2 R' U' R U R' U' R U' R'",
+    "R U2 R2 U' R2 U' R2 U2 R",
+    "R2 D' R U2 R' D R U2 R",
+    "r U R' U' r' F R F'",
+    "F' r U R' U' r' F R",
+    "R U2 R' U' R U' R'",
+    "R U R' U R U2 R'",
+    "r U R' U' r' R U R U' R'",
+    "R U R' U' R U' R' F' U' F R U R'",
+    "F R' F R2 U' R' U' R U R' F2",
+    "R' U' F U R U' R' F' R",
+    "L U F' U' L' U L F L'",
+    "R U R' U' R' F R F'",
+    "R U R2 U' R' F R U R U' F'",
+    "R U2 R2 F R F' R U2 R'",
+    "L' U' L U' L' U L U L F' L' F",
+    "F R' F' R U R U' R'",
+    "R U R' U R U' R' U' R' F R F'",
+    "L F' L' U' L U F U' L'",
+    "R' F R U R' U' F' U R",
+    "R U R' U R U2 R' F R U R' U' F'",
+    "R' U' R U' R' U2 R F R U R' U' F'",
+    "F' U' L' U L F",
+    "F U R U' R' F'",
+    "F R U R' U' F'",
+    "R' U' R' F R F' U R",
+    "R' U' R' F R F' R' F R F' U R",
+    "F R U R' U' R U R' U' F'",
+    "r U' r2 U r2 U r2 U' r",
+    "r' U r2 U' r2 U' r2 U r'",
+    "F U R U' R' U R U' R' F'",
+    "R U R' U R U' B U' B' R'",
+    "l' U2 L U L' U' L U L' U l",
+    "r U2 R' U' R U R' U' R U' r'",
+    "R' F R U R U' R2 F' R2 U' R' U R U R'",
+    "r' U' r U' R' U R U' R' U R r' U r",
+    "R U R' U' M' U R U' r'",
+];
+
+/// Permuting the last layer, by the usual names.
+const PLL: [(&str, &str); 21] = [
+    ("Aa", "x R' U R' D2 R U' R' D2 R2 x'"),
+    ("Ab", "x R2 D2 R U R' D2 R U' R x'"),
+    ("E", "x' R U' R' D R U R' D' R U R' D R U' R' D' x"),
+    ("F", "R' U' F' R U R' U' R' F R2 U' R' U' R U R' U R"),
+    ("Ga", "R2 U R' U R' U' R U' R2 U' D R' U R D'"),
+    ("Gb", "R' U' R U D' R2 U R' U R U' R U' R2 D"),
+    ("Gc", "R2 U' R U' R U R' U R2 U D' R U' R' D"),
+    ("Gd", "R U R' U' D R2 U' R U' R' U R' U R2 D'"),
+    ("H", "M2 U M2 U2 M2 U M2"),
+    ("Ja", "x R2 F R F' R U2 r' U r U2 x'"),
+    ("Jb", "R U R' F' R U R' U' R' F R2 U' R' U'"),
+    (
+        "Na",
+        "R U R' U R U R' F' R U R' U' R' F R2 U' R' U2 R U' R'",
+    ),
+    ("Nb", "R' U R U' R' F' U' F R U R' F R' F' R U' R"),
+    ("Ra", "R U' R' U' R U R D R' U' R D' R' U2 R' U'"),
+    ("Rb", "R2 F R U R U' R' F' R U2 R' U2 R"),
+    ("T", "R U R' U' R' F R2 U' R' U' R U R' F'"),
+    ("Ua", "M2 U M U2 M' U M2"),
+    ("Ub", "M2 U' M U2 M' U' M2"),
+    ("V", "R' U R' U' y R' F' R2 U' R' U R' F R F"),
+    ("Y", "F R U' R' U' R U R' F' R U R' U' R' F R F'"),
+    ("Z", "M' U M2 U M2 U M' U2 M2"),
+];
+
+/// Taking a pair out of the front right slot.
+const TAKE_OUT: &str = "R U R'";
+
+/// A stage of the CFOP method.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CfopStage {
+    /// The edges of the cross color on the bottom, matching the centers
+    /// beside them.
+    Cross,
+    /// A corner and an edge inserted together into the first two layers,
+    /// numbered from 1 to 4 in the order they are inserted.
+    Pair(usize),
+    /// Orienting the last layer, so its color faces up.
+    Oll,
+    /// Permuting the last layer, solving the cube.
+    Pll,
+}
+
+impl fmt::Display for CfopStage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CfopStage::Cross => write!(f, "Cross"),
+            CfopStage::Pair(n) => write!(f, "F2L pair {}", n),
+            CfopStage::Oll => write!(f, "OLL"),
+            CfopStage::Pll => write!(f, "PLL"),
+        }
+    }
+}
+
+/// The moves solving one stage of CFOP.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CfopStep {
+    stage: CfopStage,
+    moves: Vec<Move>,
+}
+
+impl CfopStep {
+    /// The stage solved by the moves.
+    pub fn stage(&self) -> CfopStage {
+        self.stage
+    }
+
+    /// The moves solving the stage, none if it was already solved.
+    pub fn moves(&self) -> &[Move] {
+        &self.moves
+    }
+
+    /// The number of moves, not counting whole cube rotations.
+    pub fn len(&self) -> usize {
+        self.moves
+            .iter()
+            .filter(|mv| mv.affected_range != LayerInner::WholeCube)
+            .count()
+    }
+
+    /// Whether the stage was already solved.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// A solution found with CFOP, one step per stage.
+///
+/// Shown one stage per line with the number of moves of each.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CfopSolution {
+    steps: Vec<CfopStep>,
+}
+
+impl CfopSolution {
+    /// The steps in the order they are done: the cross, four pairs, OLL and
+    /// PLL.
+    pub fn steps(&self) -> &[CfopStep] {
+        &self.steps
+    }
+
+    /// Every move of the solution in order.
+    pub fn moves(&self) -> Vec<Move> {
+        self.steps
+            .iter()
+            .flat_map(|s| s.moves.iter().copied())
+            .collect()
+    }
+
+    /// The number of moves, not counting whole cube rotations.
+    pub fn len(&self) -> usize {
+        self.steps.iter().map(CfopStep::len).sum()
+    }
+
+    /// Whether the cube was already solved.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl fmt::Display for CfopSolution {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for step in &self.steps {
+            let moves = Algorithm::from(step.moves.clone());
+            writeln!(f, "{} ({}): {}", step.stage, step.len(), moves)?;
+        }
+        write!(f, "Total: {}", self.len())
+    }
+}
+
+/// Finds solutions of a 3x3x3 with CFOP, the method most speedcubers use.
+///
+/// The cube is first turned so the center of the cross color is at the bottom
+/// and the cross is solved in the fewest turns. Each of the four pairs of the
+/// first two layers is then inserted with one of the 41 cases, choosing the
+/// slot that takes the fewest moves, and the last layer is solved with one of
+/// the 57 OLL and one of the 21 PLL algorithms. Solutions are usually 50 to 70
+/// moves long.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CfopSolver {
+    cross_color: Color,
+}
+
+impl Default for CfopSolver {
+    fn default() -> Self {
+        CfopSolver {
+            cross_color: Color::White,
+        }
+    }
+}
+
+impl CfopSolver {
+    /// Create a CFOP solver starting with a white cross.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start with the cross of a different color.
+    pub fn with_cross_color(mut self, color: Color) -> Self {
+        self.cross_color = color;
+        self
+    }
+
+    /// The moves solving each stage of a 3x3x3.
+    ///
+    /// The whole cube rotation putting the cross color at the bottom is part
+    /// of the [`CfopStage::Cross`] moves. Returns [`Error::Unsolvable`] if the
+    /// cube can't be solved by turning the sides, and [`Error::StepNotFound`]
+    /// if a stage gets stuck.
+    pub fn solve(&self, cube: &Cube) -> Result<CfopSolution, Error> {
+        let (rotation, state) = hold_down(cube, self.cross_color)?;
+        let mut solving = Solving::new(state);
+        let mut steps = Vec::with_capacity(7);
+
+        cross(&mut solving)?;
+        let mut moves = solving.take();
+        moves.splice(0..0, rotation.iter().copied());
+        steps.push(CfopStep {
+            stage: CfopStage::Cross,
+            moves,
+        });
+        for n in 1..=4 {
+            insert_pair(&mut solving)?;
+            steps.push(CfopStep {
+                stage: CfopStage::Pair(n),
+                moves: solving.take(),
+            });
+        }
+        orient_last_layer(&mut solving)?;
+        steps.push(CfopStep {
+            stage: CfopStage::Oll,
+            moves: solving.take(),
+        });
+        permute_last_layer(&mut solving)?;
+        steps.push(CfopStep {
+            stage: CfopStage::Pll,
+            moves: solving.take(),
+        });
+        debug_assert!(solving.cube.is_solved());
+        Ok(CfopSolution { steps })
+    }
+}
+
+/// The fewest turns solving the cross edges from every class of placements
+/// and flips.
+fn cross_distances() -> &'static (EdgeMoves, Nibbles) {
+    static TABLE: OnceLock<(EdgeMoves, Nibbles)> = OnceLock::new();
+    TABLE.get_or_init(|| {
+        let pieces: Vec<u8> = CROSS.iter().map(|&e| e as u8).collect();
+        let moves = EdgeMoves::new(&pieces);
+        let distances = moves.distances();
+        (moves, distances)
+    })
+}
+
+/// The fewest turns needed to solve the cross.
+fn cross_distance(cube: &CubieCube) -> u8 {
+    let (moves, distances) = cross_distances();
+    let (placement, flips) = moves.coordinate(cube);
+    distances.get(moves.index(placement, flips))
+}
+
+fn cross(solving: &mut Solving) -> Result<(), Error> {
+    let mut togo = cross_distance(&solving.cube);
+    while togo > 0 {
+        let m = (0..18)
+            .find(|&m| cross_distance(&solving.cube.turn(m)) < togo)
+            .ok_or(Error::StepNotFound("get closer to the cross"))?;
+        solving.apply(&[m]);
+        togo -= 1;
+    }
+    Ok(())
+}
+
+fn cross_solved(cube: &CubieCube) -> bool {
+    CROSS.iter().all(|&e| edge_solved(cube, e))
+}
+
+fn pair_solved(cube: &CubieCube, slot: usize) -> bool {
+    corner_solved(cube, BOTTOM_CORNERS[slot]) && edge_solved(cube, MIDDLE_EDGES[slot])
+}
+
+/// Whether the corner and edge of a pair are each on top or in its own slot,
+/// so that one of the cases inserts them.
+fn pair_free(cube: &CubieCube, slot: usize) -> bool {
+    let corner = cube
+        .cp
+        .iter()
+        .position(|&k| k as usize == BOTTOM_CORNERS[slot]);
+    let edge = cube
+        .ep
+        .iter()
+        .position(|&e| e as usize == MIDDLE_EDGES[slot]);
+    matches!(corner, Some(at) if at < 4 || at == BOTTOM_CORNERS[slot])
+        && matches!(edge, Some(at) if at < 4 || at == MIDDLE_EDGES[slot])
+}
+
+fn insert_pair(solving: &mut Solving) -> Result<(), Error> {
+    let before = solving.cube;
+    let solved: Vec<usize> = (0..4).filter(|&s| pair_solved(&before, s)).collect();
+    let kept = |c: &CubieCube| cross_solved(c) && solved.iter().all(|&s| pair_solved(c, s));
+    let open: Vec<usize> = (0..4).filter(|s| !solved.contains(s)).collect();
+    if open.is_empty() {
+        return Ok(());
+    }
+    if !open.iter().any(|&s| pair_free(&before, s)) {
+        // The pieces of every pair are stuck in the wrong slots.
+        let mut take_out = Vec::with_capacity(open.len() * 4);
+        for &slot in &open {
+            let turns = alg(TAKE_OUT, slot)?;
+            for quarters in 0..4 {
+                take_out.push([up_turns(quarters), turns.clone()].concat());
+            }
+        }
+        let found = solving.reach(&take_out, 2, |c| {
+            kept(c) && open.iter().any(|&s| pair_free(c, s))
+        });
+        if !found {
+            return Err(Error::StepNotFound("take out a pair"));
+        }
+    }
+    let before = solving.cube;
+    let mut best: Option<Vec<usize>> = None;
+    for &slot in &open {
+        for quarters in 0..4 {
+            for case in F2L.iter() {
+                let mut turns = up_turns(quarters);
+                turns.extend(alg(case, slot)?);
+                if best.as_ref().is_some_and(|b| b.len() <= turns.len()) {
+                    continue;
+                }
+                let after = turns.iter().fold(before, |c, &m| c.turn(m));
+                if kept(&after) && pair_solved(&after, slot) {
+                    best = Some(turns);
+                }
+            }
+        }
+    }
+    solving.apply(&best.ok_or(Error::StepNotFound("insert a pair"))?);
+    Ok(())
+}
+
+fn last_layer_oriented(cube: &CubieCube) -> bool {
+    first_two_layers(cube)
+        && cube.co[..4].iter().all(|&o| o == 0)
+        && cube.eo[..4].iter().all(|&o| o == 0)
+}
+
+/// Every face turn of last layer algorithms after each turn of the top.
+fn candidates<'a>(algs: impl Iterator<Item = &'a str>) -> Result<Vec<Vec<usize>>, Error> {
+    let turns = algs
+        .map(|notation| alg(notation, 0))
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok((0..4)
+        .flat_map(|quarters| {
+            turns
+                .iter()
+                .map(move |turns| [up_turns(quarters), turns.clone()].concat())
+        })
+        .collect())
+}
+
+fn orient_last_layer(solving: &mut Solving) -> Result<(), Error> {
+    let found = solving.reach(&candidates(OLL.iter().copied())?, 1, last_layer_oriented);
+    if !found {
+        return Err(Error::StepNotFound("orient the last layer"));
+    }
+    Ok(())
+}
+
+/// The turn of the top solving a cube with the last layer permuted, if any.
+fn alignment(cube: &CubieCube) -> Option<usize> {
+    (0..4).find(|&quarters| {
+        up_turns(quarters)
+            .iter()
+            .fold(*cube, |c, &m| c.turn(m))
+            .is_solved()
+    })
+}
+
+fn permute_last_layer(solving: &mut Solving) -> Result<(), Error> {
+    let algs = candidates(PLL.iter().map(|&(_, notation)| notation))?;
+    let found = solving.reach(&algs, 1, |c| alignment(c).is_some());
+    let quarters = alignment(&solving.cube)
+        .filter(|_| found)
+        .ok_or(Error::StepNotFound("permute the last layer"))?;
+    solving.apply(&up_turns(quarters));
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::method::inverse;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+    use std::collections::HashSet;
+
+    /// The state an algorithm solves.
+    fn case(notation: &str) -> CubieCube {
+        inverse(&alg(notation, 0).unwrap())
+            .iter()
+            .fold(CubieCube::SOLVED, |c, &m| c.turn(m))
+    }
+
+    /// The states reached by each turn of the top.
+    fn turns_of_top(cube: &CubieCube) -> Vec<CubieCube> {
+        (0..4)
+            .map(|q| up_turns(q).iter().fold(*cube, |c, &m| c.turn(m)))
+            .collect()
+    }
+
+    #[test]
+    fn test_f2l() {
+        let mut cases = HashSet::new();
+        for notation in F2L.iter() {
+            let cube = case(notation);
+            assert!(cross_solved(&cube), "{}", notation);
+            assert!((1..4).all(|s| pair_solved(&cube, s)), "{}", notation);
+            assert!(!pair_solved(&cube, 0), "{}", notation);
+            let key = turns_of_top(&cube)
+                .iter()
+                .map(|c| {
+                    let corner = c.cp.iter().position(|&k| k == 4).unwrap();
+                    let edge = c.ep.iter().position(|&e| e == 8).unwrap();
+                    (corner, c.co[corner], edge, c.eo[edge])
+                })
+                .min();
+            assert!(cases.insert(key), "{}", notation);
+        }
+    }
+
+    #[test]
+    fn test_last_layer() {
+        let mut cases = HashSet::new();
+        for notation in OLL.iter() {
+            let cube = case(notation);
+            assert!(first_two_layers(&cube), "{}", notation);
+            assert!(!last_layer_oriented(&cube), "{}", notation);
+            let key = turns_of_top(&cube).iter().map(|c| (c.co, c.eo)).min();
+            assert!(cases.insert(key), "{}", notation);
+        }
+
+        let mut cases = HashSet::new();
+        for (name, notation) in PLL.iter() {
+            let cube = case(notation);
+            assert!(last_layer_oriented(&cube), "{}", name);
+            assert!(alignment(&cube).is_none(), "{}", name);
+            // Cases are the same whichever way the top is turned before or after.
+            let key = turns_of_top(&cube)
+                .iter()
+                .flat_map(|c| turns_of_top(&c.inverse()))
+                .map(|c| (c.cp, c.ep))
+                .min();
+            assert!(cases.insert(key), "{}", name);
+        }
+    }
+
+    #[test]
+    fn test_cross() {
+        let mut solving = Solving::new(CubieCube::SOLVED);
+        solving.apply(&alg("D R F2 L' U F", 0).unwrap());
+        let distance = cross_distance(&solving.cube);
+        assert!(distance <= 6);
+        let mut searched = Solving::new(solving.cube);
+        assert!(searched.search(distance as usize, cross_solved));
+        assert!(!Solving::new(solving.cube).search(distance as usize - 1, cross_solved));
+        solving.take();
+        cross(&mut solving).unwrap();
+        assert!(cross_solved(&solving.cube));
+        assert_eq!(solving.take().len(), distance as usize);
+    }
+
+    #[test]
+    fn test_solve() {
+        let solver = CfopSolver::new();
+        let solution = solver.solve(&Cube::new3x3x3()).unwrap();
+        assert_eq!(solution.steps().len(), 7);
+        assert!(solution.is_empty());
+
+        let mut rng = ChaCha8Rng::seed_from_u64(17);
+        for _ in 0..20 {
+            let state = CubieCube::random(&mut rng);
+            let mut cube = state.to_cube();
+            let solution = solver.solve(&cube).unwrap();
+            let cross_length = cross_distance(&state) as usize;
+            assert_eq!(solution.steps()[0].len(), cross_length);
+            assert!(cross_length <= 8);
+            assert!(solution.len() < 100, "{}", solution);
+            let mut pairs = 0;
+            for step in solution.steps() {
+                cube.apply(&Algorithm::from(step.moves().to_vec())).unwrap();
+                let held = CubieCube::from_cube(&cube).unwrap();
+                let pairs_solved = (0..4).filter(|&s| pair_solved(&held, s)).count();
+                match step.stage() {
+                    CfopStage::Cross => assert!(cross_solved(&held)),
+                    CfopStage::Pair(n) => {
+                        assert!(cross_solved(&held) && pairs_solved >= n);
+                        pairs = pairs_solved;
+                    }
+                    CfopStage::Oll => assert!(last_layer_oriented(&held)),
+                    CfopStage::Pll => assert!(held.is_solved()),
+                }
+            }
+            assert_eq!(pairs, 4);
+            assert!(cube.is_solved());
+        }
+    }
+
+    #[test]
+    fn test_cross_color() {
+        let scrambled = || {
+            let mut cube = Cube::new3x3x3();
+            cube.apply(&"R U2 F' L D B2 U' R2".parse().unwrap())
+                .unwrap();
+            cube
+        };
+        for &color in [Color::Yellow, Color::Red, Color::Blue, Color::Green].iter() {
+            let mut cube = scrambled();
+            let solution = CfopSolver::new()
+                .with_cross_color(color)
+                .solve(&cube)
+                .unwrap();
+            cube.apply(&Algorithm::from(solution.moves())).unwrap();
+            assert!(cube.is_solved(), "{:?}", color);
+        }
+
+        let cube = scrambled();
+
+        let text = CfopSolver::new().solve(&cube).unwrap().to_string();
+        assert!(text.starts_with("Cross ("));
+        assert!(text.contains("\nF2L pair 4 ("));
+        assert!(text.contains("\nPLL ("));
+
+        assert_eq!(
+            CfopSolver::new().solve(&Cube::new2x2x2()),
+            Err(Error::UnsupportedSides {
+                expected: 3,
+                found: 2
+            })
+        );
+    }
+}
@@ -0,0 +1,151 @@
+use crate::cubie::{CubieCube, FACE_TURNS};
+use std::sync::OnceLock;
+
+/// Where each value of a coordinate goes after every face turn.
+pub(crate) type MoveTable = Vec<[u16; FACE_TURNS]>;
+
+/// The face turns that keep the cube in the group of states reachable with
+/// `U`, `D`, `R2`, `L2`, `F2` and `B2`.
+pub(crate) const PHASE2_TURNS: [usize; 10] = [0, 1, 2, 4, 7, 9, 10, 11, 13, 16];
+
+pub(crate) const TWISTS: usize = 2187;
+pub(crate) const FLIPS: usize = 2048;
+pub(crate) const SLICES: usize = 495;
+pub(crate) const CORNERS: usize = 40320;
+pub(crate) const UD_EDGES: usize = 40320;
+pub(crate) const SLICE_EDGES: usize = 24;
+
+/// Move tables of every coordinate of [`CubieCube`], the edge orders only for
+/// the turns in [`PHASE2_TURNS`].
+pub(crate) struct MoveTables {
+    pub(crate) twist: MoveTable,
+    pub(crate) flip: MoveTable,
+    pub(crate) slice: MoveTable,
+    pub(crate) corners: MoveTable,
+    pub(crate) ud_edges: MoveTable,
+    pub(crate) slice_edges: MoveTable,
+}
+
+/// The move tables, made the first time they are needed.
+pub(crate) fn move_tables() -> &'static MoveTables {
+    static TABLES: OnceLock<MoveTables> = OnceLock::new();
+    TABLES.get_or_init(|| {
+        let all: Vec<usize> = (0..FACE_TURNS).collect();
+        MoveTables {
+            twist: move_table(TWISTS, &all, CubieCube::set_twist, CubieCube::twist),
+            flip: move_table(FLIPS, &all, CubieCube::set_flip, CubieCube::flip),
+            slice: move_table(SLICES, &all, CubieCube::set_slice, CubieCube::slice),
+            corners: move_table(CORNERS, &all, CubieCube::set_corners, CubieCube::corners),
+            ud_edges: move_table(
+                UD_EDGES,
+                &PHASE2_TURNS,
+                CubieCube::set_ud_edges,
+                CubieCube::ud_edges,
+            ),
+            slice_edges: move_table(
+                SLICE_EDGES,
+                &PHASE2_TURNS,
+                CubieCube::set_slice_edges,
+                CubieCube::slice_edges,
+            ),
+        }
+    })
+}
+
+/// Where each of `size` values of a coordinate goes after each of the turns,
+/// leaving the other turns at `0`.
+pub(crate) fn move_table(
+    size: usize,
+    turns: &[usize],
+    set: fn(&mut CubieCube, usize),
+    get: fn(&CubieCube) -> usize,
+) -> MoveTable {
+    (0..size)
+        .map(|i| {
+            let mut cube = CubieCube::SOLVED;
+            set(&mut cube, i);
+            let mut row = [0; FACE_TURNS];
+            for &m in turns {
+                row[m] = get(&cube.turn(m)) as u16;
+            }
+            row
+        })
+        .collect()
+}
+
+/// The fewest turns needed to bring a pair of coordinates to `(0, 0)`, indexed
+/// by `a * b_size + b`.
+pub(crate) fn prune_table(a: &MoveTable, b: &MoveTable, turns: &[usize]) -> Vec<u8> {
+    let b_size = b.len();
+    let mut depths = vec![u8::MAX; a.len() * b_size];
+    depths[0] = 0;
+    let mut frontier = vec![0];
+    let mut depth = 0;
+    while !frontier.is_empty() {
+        depth += 1;
+        let mut next = Vec::new();
+        for index in frontier {
+            let (i, j) = (index / b_size, index % b_size);
+            for &m in turns {
+                let to = a[i][m] as usize * b_size + b[j][m] as usize;
+                if depths[to] == u8::MAX {
+                    depths[to] = depth;
+                    next.push(to);
+                }
+            }
+        }
+        frontier = next;
+    }
+    depths
+}
+
+/// Whether turning face turn `m` right after `last` could be left out or done
+/// in a shorter way. Turns of the same side are merged and turns of opposite
+/// sides are only searched in one order.
+pub(crate) fn is_redundant(last: Option<usize>, m: usize) -> bool {
+    match last {
+        Some(last) => m / 3 == last / 3 || m / 3 + 3 == last / 3,
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_move_tables() {
+        let tables = move_tables();
+        let mut cube = CubieCube::SOLVED;
+        for &m in [1, 3, 8, 12, 16, 5, 9].iter() {
+            let turned = cube.turn(m);
+            assert_eq!(tables.twist[cube.twist()][m] as usize, turned.twist());
+            assert_eq!(tables.flip[cube.flip()][m] as usize, turned.flip());
+            assert_eq!(tables.slice[cube.slice()][m] as usize, turned.slice());
+            assert_eq!(tables.corners[cube.corners()][m] as usize, turned.corners());
+            cube = turned;
+        }
+        let mut cube = CubieCube::SOLVED;
+        for &m in PHASE2_TURNS.iter().rev() {
+            let turned = cube.turn(m);
+            assert_eq!(
+                tables.ud_edges[cube.ud_edges()][m] as usize,
+                turned.ud_edges()
+            );
+            assert_eq!(
+                tables.slice_edges[cube.slice_edges()][m] as usize,
+                turned.slice_edges()
+            );
+            cube = turned;
+        }
+    }
+
+    #[test]
+    fn test_is_redundant() {
+        assert!(!is_redundant(None, 0));
+        assert!(is_redundant(Some(0), 2));
+        assert!(is_redundant(Some(9), 1));
+        assert!(!is_redundant(Some(1), 9));
+        assert!(!is_redundant(Some(3), 0));
+    }
+}
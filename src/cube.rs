@@ -1,16 +1,26 @@
 use crate::{
-    cubit::Cubit,
+    algorithm::Algorithm,
+    cubie::CubieCube,
+    cubit::{self, Cubit},
     error::Error,
-    movement::{LayerInner, Move},
+    facelet::{Face, Facelets, NetStyle},
+    movement::{Axis, LayerInner, Move},
+    orientation::Orientation,
+    piece::{Orbit, Piece, PieceId, PieceKind, PieceType, Position},
 };
-use nalgebra::Vector3;
-use std::ops::RangeInclusive;
+use nalgebra::{Matrix3, Vector3};
+use std::{collections::BTreeMap, convert::TryFrom, ops::RangeInclusive};
+
+/// The most cubits on an edge of a cube, positions have to fit in 16 bits.
+pub(crate) const MAX_SIDES: usize = 1 << 15;
 
 /// Represents a full Puzzle Cube.
 #[derive(Debug)]
 #[cfg_attr(test, derive(PartialEq, Eq))]
 pub struct Cube {
     sides: usize,
+    // Kept in the order of the places they are at, see `Cube::shell_index`, so
+    // a move only has to look at the cubits in the layers it turns.
     cubits: Vec<Cubit>,
 }
 
@@ -22,27 +32,20 @@ impl Cube {
         if sides < 2 {
             return Err(Error::InvalidNumberSides(sides));
         }
-        let full = sides.pow(3);
-        let size = full - (sides - 2).pow(3);
+        if sides > MAX_SIDES {
+            return Err(Error::TooManySides(sides));
+        }
         let mut cube = Cube {
             sides,
             cubits: Vec::with_capacity(0),
         };
-        let cubits = (0..full)
-            .filter_map(|i| {
-                if ((i / cube.sides) % cube.sides) % (cube.sides - 1) == 0
-                    || (i % cube.sides) % (cube.sides - 1) == 0
-                    || (i / cube.sides.pow(2)) % (cube.sides - 1) == 0
-                {
-                    Some(Cubit::std_from_position(cube.index_to_coords(i)))
-                } else {
-                    None
-                }
-            })
-            .fold(Vec::with_capacity(size), |mut v, c| {
-                v.push(c);
-                v
-            });
+        // Layers from the bottom up are in the order of the shell index.
+        let cubits = cube
+            .full_range()
+            .step_by(cube.step())
+            .flat_map(|z| cube.layer_positions(2, z))
+            .map(Cubit::std_from_position)
+            .collect();
         cube.cubits = cubits;
         Ok(cube)
     }
@@ -57,61 +60,527 @@ impl Cube {
         Self::with_number_sides(3).expect("3 is a valid number of sides")
     }
 
+    /// The number of cubits on an edge of the cube.
+    pub fn sides(&self) -> usize {
+        self.sides
+    }
+
+    /// Create a cube from its stickers, written as in [`Facelets`]'s `Display`.
+    ///
+    /// Each sticker is the letter of the side that has its color when solved,
+    /// whitespace is ignored. Pieces are placed and turned to show the stickers
+    /// but the cube is not checked to be reachable by turning the sides, see
+    /// [`Cube::validate`].
+    pub fn from_facelets(sides: usize, facelets: &str) -> Result<Self, Error> {
+        let mut cube = Self::with_number_sides(sides)?;
+        let colors = facelets
+            .chars()
+            .filter(|c| !c.is_whitespace())
+            .enumerate()
+            .map(|(index, found)| {
+                Face::from_letter(found)
+                    .map(Face::color)
+                    .ok_or(Error::InvalidFacelet { index, found })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let per_face = sides * sides;
+        if colors.len() != 6 * per_face {
+            return Err(Error::InvalidFaceletCount {
+                expected: 6 * per_face,
+                found: colors.len(),
+            });
+        }
+        for face in Face::ALL.iter() {
+            let found = colors.iter().filter(|&&c| c == face.color()).count();
+            if found != per_face {
+                return Err(Error::InvalidColorCount {
+                    color: face.color(),
+                    expected: per_face,
+                    found,
+                });
+            }
+        }
+
+        // Stickers of every position as the facelet index, the direction the
+        // sticker faces and the direction it faces when solved.
+        let mut stickers = vec![Vec::new(); cube.cubits.len()];
+        for (i, color) in colors.iter().enumerate() {
+            let face = Face::ALL[i / per_face];
+            let pos = cube.sticker_position(face, (i % per_face) / sides, i % sides);
+            stickers[cube.shell_index(&pos)].push((i, face.normal(), color.home_face().normal()));
+        }
+
+        // Every piece of a solved cube is used once, found by where it is on
+        // the solved cube.
+        let rotations = cubit::rotations();
+        let mut used = vec![false; cube.cubits.len()];
+        for (i, stickers) in stickers.iter().enumerate() {
+            let pos = cube.cubits[i].get_position();
+            let facelet = stickers[0].0;
+            let mut turns = (0..)
+                .zip(rotations.iter())
+                .filter(|(_, rot)| stickers.iter().all(|(_, n, d)| &(*rot * d) == n))
+                .peekable();
+            if turns.peek().is_none() {
+                return Err(Error::InvalidPiece { facelet });
+            }
+            // Only centers can be turned more than one way, any matching center
+            // that isn't used yet will do.
+            let (rot, home) = turns
+                .map(|(i, rot)| (i, cube.shell_index(&(rot.transpose() * pos))))
+                .find(|(_, home)| !used[*home])
+                .ok_or(Error::DuplicatePiece { facelet })?;
+            used[home] = true;
+            cube.cubits[i] = Cubit::with_orientation(pos, rot);
+        }
+        Ok(cube)
+    }
+
     /// Rotate the cube or sides given the move passed in.
     pub fn rotate(&mut self, mv: &Move) -> Result<(), Error> {
-        match &mv.affected_range {
-            LayerInner::Single(l) if l >= &self.sides => {
-                return Err(Error::InvalidMoveLayer);
+        self.check_layer(mv)?;
+        self.rotate_unchecked(mv);
+        Ok(())
+    }
+
+    /// Do every move of the algorithm in order.
+    ///
+    /// All moves are checked before any are made so the cube is left untouched
+    /// if one of them is invalid for this cube.
+    pub fn apply(&mut self, alg: &Algorithm) -> Result<(), Error> {
+        for mv in alg {
+            self.check_layer(mv)?;
+        }
+        for mv in alg {
+            self.rotate_unchecked(mv);
+        }
+        Ok(())
+    }
+
+    /// Whether every side of the cube shows a single color.
+    ///
+    /// The cube may be held in any orientation. Center pieces only show a single
+    /// sticker, so pieces of the same color may be swapped with each other or
+    /// turned in place and the cube still counts as solved.
+    pub fn is_solved(&self) -> bool {
+        let offset = self.offset() as isize;
+        // The first cubit is a corner, its three stickers fix how the cube is held.
+        let held = self.cubits[0].orientation();
+        self.cubits.iter().all(|c| {
+            let home = c.home_position();
+            let orientation = c.orientation();
+            // Every sticker must face the same side as its color does on the first cubit.
+            (0..3).all(|i| home[i].abs() != offset || orientation.column(i) == held.column(i))
+        })
+    }
+
+    /// How the cube is held, which colors are on top and at the front.
+    ///
+    /// For cubes with an odd number of sides this is where the middle centers
+    /// are. Cubes with an even number of sides have no fixed centers, so they
+    /// are held the way the corner belonging at the down, back and left place
+    /// is turned. Turning the up, right and front sides keeps it where it is,
+    /// turning the down, left or back side turns it and how the cube is held.
+    pub fn orientation(&self) -> Result<Orientation, Error> {
+        if self.even_sides() {
+            let dbl = Vector3::repeat(-(self.offset() as isize));
+            let corner = self.cubit_from(&dbl).expect("every piece is on the cube");
+            let held = Orientation::try_from(corner.orientation());
+            return Ok(held.expect("cubits are turned by rotations"));
+        }
+        let offset = self.offset() as isize;
+        // The colors at the front, right and top are the rows of the rotation.
+        let home = |face: Face| {
+            let c = &self.cubits[self.shell_index(&(face.normal() * offset))];
+            (c.home_position() / offset).transpose()
+        };
+        let rows = [home(Face::Front), home(Face::Right), home(Face::Up)];
+        Orientation::try_from(Matrix3::from_rows(&rows)).map_err(|_| Error::InvalidCenters)
+    }
+
+    /// Check that the cube is one that can be held in the hand and, for a
+    /// 3x3x3, that it can be solved by turning the sides.
+    ///
+    /// Every piece must be at its own place on the outside of the cube and the
+    /// pieces must be those of a solved cube. A 3x3x3
+    /// also can't have a single corner twisted, a single edge flipped or two
+    /// pieces swapped. The first problem found is returned, places are named
+    /// by the index of one of their facelets or as in [`CubieCube`].
+    pub fn validate(&self) -> Result<(), Error> {
+        let offset = self.offset() as isize;
+        let step = self.step() as isize;
+        let mut at = vec![None; self.sides.pow(3) - (self.sides - 2).pow(3)];
+        for (i, c) in self.cubits.iter().enumerate() {
+            let pos = c.get_position();
+            let on_grid = pos
+                .iter()
+                .all(|v| v.abs() <= offset && (v + offset) % step == 0);
+            if !on_grid || pos.iter().all(|v| v.abs() != offset) {
+                return Err(Error::InvalidPosition {
+                    position: [pos[0], pos[1], pos[2]],
+                });
+            }
+            let place = &mut at[self.shell_index(&pos)];
+            if place.is_some() {
+                let facelet = self.facelet_at(&pos);
+                return Err(Error::OverlappingPieces { facelet });
+            }
+            *place = Some(i);
+        }
+        if let Some(empty) = at.iter().position(Option::is_none) {
+            let facelet = (0..6 * self.sides.pow(2))
+                .find(|&f| {
+                    let (face, row, col) = self.facelet_coords(f);
+                    self.shell_index(&self.sticker_position(face, row, col)) == empty
+                })
+                .expect("every place has a facelet");
+            return Err(Error::MissingPiece { facelet });
+        }
+        let mut used = vec![false; at.len()];
+        for &i in at.iter().flatten() {
+            let c = &self.cubits[i];
+            let home = &mut used[self.shell_index(&c.home_position())];
+            if *home {
+                let facelet = self.facelet_at(&c.get_position());
+                return Err(Error::DuplicatePiece { facelet });
+            }
+            *home = true;
+        }
+        if !self.even_sides() {
+            // The middle centers must be turned together by one rotation.
+            let home = |face: Face| {
+                let place = self.shell_index(&(face.normal() * offset));
+                let c = &self.cubits[at[place].expect("every place has a piece")];
+                c.home_position() / offset
+            };
+            if home(Face::Front).cross(&home(Face::Right)) != home(Face::Up)
+                || Face::ALL[..3]
+                    .iter()
+                    .zip(&Face::ALL[3..])
+                    .any(|(&face, &opposite)| home(face) != -home(opposite))
+            {
+                return Err(Error::InvalidCenters);
+            }
+        }
+        if self.sides == 3 {
+            CubieCube::from_cube(self)?.check_solvable()?;
+        }
+        Ok(())
+    }
+
+    /// Every piece of the cube, in the order of the positions they are at.
+    pub fn pieces(&self) -> impl Iterator<Item = Piece> + '_ {
+        self.cubits.iter().map(move |c| self.piece(c))
+    }
+
+    /// Every piece of a type, in the order of the positions they are at.
+    pub fn pieces_of(&self, piece_type: PieceType) -> impl Iterator<Item = Piece> + '_ {
+        self.pieces().filter(move |p| p.piece_type() == piece_type)
+    }
+
+    /// The piece at a position, if it is a place on the outside of the cube.
+    pub fn piece_at(&self, position: Position) -> Option<Piece> {
+        let pos = self.coords(position)?;
+        Some(self.piece(&self.cubits[self.shell_index(&pos)]))
+    }
+
+    /// The position a piece is at, if it is a piece of the cube.
+    pub fn location_of(&self, id: PieceId) -> Option<Position> {
+        let home = self.coords(id.home())?;
+        self.cubit_from(&home)
+            .map(|c| self.position(&c.get_position()))
+    }
+
+    /// The cubit whose place on a solved cube is a position.
+    fn cubit_from(&self, home: &Vector3<isize>) -> Option<&Cubit> {
+        // A cubit turned by a rotation is at the place the rotation turns its
+        // home to.
+        (0..)
+            .zip(cubit::rotations().iter())
+            .map(|(index, rot)| (index, &self.cubits[self.shell_index(&(rot * home))]))
+            .find(|(index, c)| c.orientation_index() == *index)
+            .map(|(_, c)| c)
+    }
+
+    /// How far each orbit of pieces is from solved, by [`PieceKind`].
+    ///
+    /// A piece is solved when it shows the stickers a solved cube held in the
+    /// cube's [`orientation`](Cube::orientation) shows, so this fails like it
+    /// for centers that are not arranged like on a real cube.
+    pub fn orbits(&self) -> Result<Vec<Orbit>, Error> {
+        let held = Matrix3::from(self.orientation()?);
+        let offset = self.offset() as isize;
+        let mut orbits = BTreeMap::new();
+        let mut kinds = Vec::with_capacity(self.cubits.len());
+        // The place the piece at each place belongs at.
+        let mut target = Vec::with_capacity(self.cubits.len());
+        for c in self.cubits.iter() {
+            let home = c.home_position();
+            let kind =
+                PieceKind::at(self.position(&home), self.sides).expect("cubits are on the outside");
+            let pos = c.get_position();
+            let orientation = c.orientation();
+            let solved =
+                (0..3).all(|i| pos[i].abs() != offset || orientation.row(i) == held.row(i));
+            let orbit = orbits.entry(kind).or_insert(Orbit {
+                kind,
+                pieces: 0,
+                solved: 0,
+                odd: Some(false),
+            });
+            orbit.pieces += 1;
+            orbit.solved += usize::from(solved);
+            kinds.push(kind);
+            target.push(self.shell_index(&(held * home)));
+        }
+        // Every cycle of an even length takes an odd number of swaps.
+        let mut seen = vec![false; target.len()];
+        for (start, kind) in kinds.iter().enumerate() {
+            let mut len = 0;
+            let mut place = start;
+            while !seen[place] {
+                seen[place] = true;
+                place = target[place];
+                len += 1;
+            }
+            if len > 0 && len % 2 == 0 {
+                let odd = &mut orbits.get_mut(kind).expect("every kind has an orbit").odd;
+                *odd = odd.map(|odd| !odd);
             }
-            LayerInner::Multiple(l) if l > &self.sides => {
-                return Err(Error::InvalidMoveLayer);
+        }
+        Ok(orbits
+            .into_iter()
+            .map(|(kind, mut orbit)| {
+                if let PieceKind::XCenter(_) | PieceKind::PlusCenter(_) | PieceKind::Oblique(..) =
+                    kind
+                {
+                    orbit.odd = None;
+                }
+                orbit
+            })
+            .collect())
+    }
+
+    /// Every sticker of the cube, see [`Facelets`] for the order they are in.
+    pub fn to_facelets(&self) -> Facelets {
+        let mut stickers = Vec::with_capacity(6 * self.sides.pow(2));
+        for face in Face::ALL.iter() {
+            for row in 0..self.sides {
+                for col in 0..self.sides {
+                    let pos = self.sticker_position(*face, row, col);
+                    let cubit = &self.cubits[self.shell_index(&pos)];
+                    // The axis vector now pointing out of this side gives the color.
+                    let home = cubit.orientation().transpose() * face.normal();
+                    let home =
+                        Face::from_normal(&home).expect("cubits only rotate by quarter turns");
+                    stickers.push(home.color());
+                }
             }
-            _ => {}
+        }
+        Facelets::new(self.sides, stickers)
+    }
+
+    /// Draw the cube unfolded into a cross, see [`Facelets::to_net`].
+    pub fn to_net(&self, style: NetStyle) -> String {
+        self.to_facelets().to_net(style)
+    }
+
+    /// The 54 character facelet string of a 3x3x3 used by Kociemba's solver.
+    ///
+    /// Stickers are in the same order as [`Cube::to_facelets`], each written as
+    /// the letter of the side whose center has the same color. This makes the
+    /// string independent of the orientation the cube is held in. Returns
+    /// [`Error::InvalidCenters`] if a color has no center.
+    pub fn to_kociemba_string(&self) -> Result<String, Error> {
+        if self.sides != 3 {
+            return Err(Error::UnsupportedSides {
+                expected: 3,
+                found: self.sides,
+            });
+        }
+        let facelets = self.to_facelets();
+        let center = |face: Face| facelets.get(face, 1, 1);
+        facelets
+            .stickers()
+            .iter()
+            .map(|&color| {
+                Face::ALL
+                    .iter()
+                    .find(|&&face| center(face) == color)
+                    .map(|face| face.letter())
+                    .ok_or(Error::InvalidCenters)
+            })
+            .collect()
+    }
+
+    fn check_layer(&self, mv: &Move) -> Result<(), Error> {
+        match &mv.affected_range.for_sides(self.sides)? {
+            LayerInner::Single(l) if l >= &self.sides => Err(Error::InvalidMoveLayer),
+            LayerInner::Multiple(l) if l > &self.sides => Err(Error::InvalidMoveLayer),
+            _ => Ok(()),
+        }
+    }
+
+    fn rotate_unchecked(&mut self, mv: &Move) {
+        let axis = match mv.axis.unsigned() {
+            Axis::X => 0,
+            Axis::Y => 1,
+            Axis::Z => 2,
         };
-        // TODO: Is there a faster way to figure out which cubits need to move.
-        // Keep in mind it may have nothing todo with ranges since this is the only
-        // place that the ranges are used
-        // May also increase perfomance by not having to clone the &usize of the layer if possible!
-        // Reducing the branches would reduce what needs to be tested in UTs
-        let (x_range, y_range, z_range) = match &mv.affected_range {
-            LayerInner::Single(l) => match &mv.axis {
-                crate::AxisInner::X => (self.pos_layer(l), self.full_range(), self.full_range()),
-                crate::AxisInner::NegX => (self.neg_layer(l), self.full_range(), self.full_range()),
-                crate::AxisInner::Y => (self.full_range(), self.pos_layer(l), self.full_range()),
-                crate::AxisInner::NegY => (self.full_range(), self.neg_layer(l), self.full_range()),
-                crate::AxisInner::Z => (self.full_range(), self.full_range(), self.pos_layer(l)),
-                crate::AxisInner::NegZ => (self.full_range(), self.full_range(), self.neg_layer(l)),
-            },
-            LayerInner::Multiple(l) => match &mv.axis {
-                crate::AxisInner::X => (self.pos_range(l), self.full_range(), self.full_range()),
-                crate::AxisInner::NegX => (self.neg_range(l), self.full_range(), self.full_range()),
-                crate::AxisInner::Y => (self.full_range(), self.pos_range(l), self.full_range()),
-                crate::AxisInner::NegY => (self.full_range(), self.neg_range(l), self.full_range()),
-                crate::AxisInner::Z => (self.full_range(), self.full_range(), self.pos_range(l)),
-                crate::AxisInner::NegZ => (self.full_range(), self.full_range(), self.neg_range(l)),
-            },
-            LayerInner::WholeCube => (self.full_range(), self.full_range(), self.full_range()),
+        let negative = mv.axis.is_negative();
+        let coords = match &mv.affected_range {
+            LayerInner::Single(l) if negative => self.neg_layer(l),
+            LayerInner::Single(l) => self.pos_layer(l),
+            LayerInner::Multiple(l) if negative => self.neg_range(l),
+            LayerInner::Multiple(l) => self.pos_range(l),
+            LayerInner::Middle => self.pos_layer(&(self.sides / 2)),
+            LayerInner::WholeCube => self.full_range(),
         };
         let rot = mv.rotation_matrix();
-        for c in self.cubits.iter_mut() {
-            let pos = c.get_position();
-            if x_range.contains(&pos[(0)])
-                && y_range.contains(&pos[(1)])
-                && z_range.contains(&pos[(2)])
-            {
-                c.rotate(rot);
+        let turn = cubit::rotation_index(rot).expect("moves turn by rotations");
+        let (rows, cols) = plane(axis);
+        // The quarter turns taking the row axis towards the column axis.
+        let quarters = match (rot[(cols, rows)], rot[(rows, rows)]) {
+            (1, _) => 1,
+            (_, -1) => 2,
+            _ => 3,
+        };
+        let n = self.sides;
+        let last = n - 1;
+        let offset = self.offset() as isize;
+        let step = self.step() as isize;
+        for coord in coords.step_by(self.step()) {
+            let layer = ((coord + offset) / step) as usize;
+            let place = |row, col| {
+                let mut grid = [layer; 3];
+                grid[rows] = row;
+                grid[cols] = col;
+                grid_index(n, grid)
+            };
+            // Layers between the outside ones only have the outermost ring.
+            let outside = layer == 0 || layer == last;
+            let rings = if outside { n / 2 } else { 1 };
+            for ring in 0..rings {
+                for i in ring..last - ring {
+                    // Each place of a ring goes a quarter of the way round it.
+                    let cycle = [
+                        place(ring, i),
+                        place(last - i, ring),
+                        place(last - ring, last - i),
+                        place(i, last - ring),
+                    ];
+                    let moved = cycle.map(|at| self.cubits[at]);
+                    for (j, c) in moved.iter().enumerate() {
+                        let mut c = *c;
+                        c.rotate(turn);
+                        self.cubits[cycle[(j + quarters) % 4]] = c;
+                    }
+                }
+            }
+            if outside && n % 2 == 1 {
+                self.cubits[place(n / 2, n / 2)].rotate(turn);
             }
         }
-        Ok(())
     }
 
-    fn index_to_coords(&self, idx: usize) -> Vector3<isize> {
+    /// Positions on the outside of the cube in the layer across an axis, `0`
+    /// for x, `1` for y and `2` for z, at a coordinate along it.
+    ///
+    /// Across the z axis the positions are in the order of their shell index.
+    fn layer_positions(&self, axis: usize, coord: isize) -> impl Iterator<Item = Vector3<isize>> {
+        let n = self.sides;
+        let offset = self.offset() as isize;
+        let step = self.step() as isize;
+        let outside = coord.abs() == offset;
+        let (rows, cols) = plane(axis);
+        (0..n).flat_map(move |row| {
+            // Layers between the outside ones only have the ring around them.
+            let edge = outside || row == 0 || row == n - 1;
+            (0..n)
+                .step_by(if edge { 1 } else { n - 1 })
+                .map(move |col| {
+                    let mut pos = Vector3::zeros();
+                    pos[axis] = coord;
+                    pos[rows] = row as isize * step - offset;
+                    pos[cols] = col as isize * step - offset;
+                    pos
+                })
+        })
+    }
+
+    /// Position of the cubit with the sticker at a row and column of a side.
+    fn sticker_position(&self, face: Face, row: usize, col: usize) -> Vector3<isize> {
+        let offset = self.offset() as isize;
+        let step = self.step() as isize;
+        face.normal() * offset
+            + face.right() * (col as isize * step - offset)
+            + face.down() * (row as isize * step - offset)
+    }
+
+    /// The side, row and column of a facelet index.
+    fn facelet_coords(&self, facelet: usize) -> (Face, usize, usize) {
+        let per_face = self.sides * self.sides;
+        let i = facelet % per_face;
+        (
+            Face::ALL[facelet / per_face],
+            i / self.sides,
+            i % self.sides,
+        )
+    }
+
+    /// The index of the first facelet of a position on the outside of the cube.
+    fn facelet_at(&self, pos: &Vector3<isize>) -> usize {
+        let offset = self.offset() as isize;
+        let step = self.step() as isize;
+        let face = *Face::ALL
+            .iter()
+            .find(|face| face.normal().dot(pos) == offset)
+            .expect("the position is on the outside of the cube");
+        let col = (face.right().dot(pos) + offset) / step;
+        let row = (face.down().dot(pos) + offset) / step;
+        face as usize * self.sides.pow(2) + row as usize * self.sides + col as usize
+    }
+
+    /// The index of a position on the outside of the cube, in the order the
+    /// cubits are created by [`Cube::with_number_sides`].
+    pub(crate) fn shell_index(&self, pos: &Vector3<isize>) -> usize {
+        let offset = self.offset() as isize;
+        let step = self.step() as isize;
+        let grid = |v: isize| ((v + offset) / step) as usize;
+        grid_index(self.sides, [grid(pos[0]), grid(pos[1]), grid(pos[2])])
+    }
+
+    /// The public view of a cubit.
+    fn piece(&self, c: &Cubit) -> Piece {
+        let home = self.position(&c.home_position());
+        Piece {
+            id: PieceId::new(home),
+            position: self.position(&c.get_position()),
+            orientation: Orientation::try_from(c.orientation())
+                .expect("cubits are turned by rotations"),
+            kind: PieceKind::at(home, self.sides).expect("cubits are on the outside"),
+        }
+    }
+
+    /// The layers a position of a cubit is in.
+    fn position(&self, pos: &Vector3<isize>) -> Position {
         let offset = self.offset() as isize;
         let step = self.step() as isize;
-        let x = ((idx / self.sides) % self.sides) as isize * step - offset;
-        let y = (idx % self.sides) as isize * step - offset;
-        let z = ((idx / self.sides.pow(2)) % self.sides) as isize * step - offset;
-        Vector3::new(x, y, z)
+        let grid = |v: isize| ((v + offset) / step) as usize;
+        Position::new(grid(pos[0]), grid(pos[1]), grid(pos[2]))
+    }
+
+    /// The position of a cubit in the layers, if it is on the outside of the cube.
+    fn coords(&self, position: Position) -> Option<Vector3<isize>> {
+        PieceType::at(position, self.sides)?;
+        let offset = self.offset() as isize;
+        let step = self.step() as isize;
+        let coord = |v: usize| v as isize * step - offset;
+        Some(Vector3::new(
+            coord(position.x),
+            coord(position.y),
+            coord(position.z),
+        ))
     }
 
     fn even_sides(&self) -> bool {
@@ -136,40 +605,161 @@ impl Cube {
 
     fn full_range(&self) -> RangeInclusive<isize> {
         let offset = self.offset() as isize;
-        RangeInclusive::new(offset * -1, offset)
+        RangeInclusive::new(-offset, offset)
     }
 
     fn neg_layer(&self, layer: &usize) -> RangeInclusive<isize> {
-        let layer = layer.clone() as isize * self.step() as isize;
-        let offset = self.offset() as isize * -1;
+        let layer = *layer as isize * self.step() as isize;
+        let offset = -(self.offset() as isize);
         RangeInclusive::new(offset + layer, offset + layer)
     }
 
     fn pos_layer(&self, layer: &usize) -> RangeInclusive<isize> {
-        let layer = layer.clone() as isize * self.step() as isize;
+        let layer = *layer as isize * self.step() as isize;
         let offset = self.offset() as isize;
         RangeInclusive::new(offset - layer, offset - layer)
     }
     fn neg_range(&self, layers: &usize) -> RangeInclusive<isize> {
-        let layers = (layers.clone() as isize - 1) * self.step() as isize;
-        let offset = self.offset() as isize * -1;
+        let layers = (*layers as isize - 1) * self.step() as isize;
+        let offset = -(self.offset() as isize);
         RangeInclusive::new(offset, offset + layers)
     }
     fn pos_range(&self, layers: &usize) -> RangeInclusive<isize> {
-        let layers = (layers.clone() as isize - 1) * self.step() as isize;
+        let layers = (*layers as isize - 1) * self.step() as isize;
         let offset = self.offset() as isize;
         RangeInclusive::new(offset - layers, offset)
     }
 }
+
+/// The two axes across the layer across an axis, as rows and columns.
+fn plane(axis: usize) -> (usize, usize) {
+    match axis {
+        0 => (1, 2),
+        1 => (0, 2),
+        _ => (0, 1),
+    }
+}
+
+/// The shell index of a place on the outside of a cube with a number of sides,
+/// from the layers it is in along each axis.
+fn grid_index(n: usize, [x, y, z]: [usize; 3]) -> usize {
+    // Whole layers at the bottom and top, only the outside ring between them.
+    let ring = 4 * (n - 1);
+    let layer_start = if z == 0 { 0 } else { n * n + (z - 1) * ring };
+    if z == 0 || z == n - 1 {
+        return layer_start + x * n + y;
+    }
+    let row_start = if x == 0 { 0 } else { n + (x - 1) * 2 };
+    if x == 0 || x == n - 1 {
+        layer_start + row_start + y
+    } else {
+        layer_start + row_start + usize::from(y == n - 1)
+    }
+}
+
+/// How a [`Cube`] is written by serde, the number of sides and the index of the
+/// rotation of each cubit from where it is on a solved cube, in the order the
+/// cubits are created by [`Cube::with_number_sides`].
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+struct CompactCube {
+    sides: usize,
+    orientations: Vec<u8>,
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for Cube {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut orientations = vec![0; self.cubits.len()];
+        for c in &self.cubits {
+            orientations[self.shell_index(&c.home_position())] = c.orientation_index();
+        }
+        CompactCube {
+            sides: self.sides,
+            orientations,
+        }
+        .serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Cube {
+    /// Read a cube, which must pass [`Cube::validate`].
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        use serde::de::{Error as _, Unexpected};
+        let compact = CompactCube::deserialize(deserializer)?;
+        let mut cube = Cube::with_number_sides(compact.sides).map_err(D::Error::custom)?;
+        if compact.orientations.len() != cube.cubits.len() {
+            let expected = format!("{} orientations", cube.cubits.len());
+            return Err(D::Error::invalid_length(
+                compact.orientations.len(),
+                &expected.as_str(),
+            ));
+        }
+        let solved = std::mem::take(&mut cube.cubits);
+        let mut placed = vec![None; solved.len()];
+        for (c, &index) in solved.iter().zip(&compact.orientations) {
+            let rot = cubit::rotations().get(index as usize).ok_or_else(|| {
+                D::Error::invalid_value(
+                    Unexpected::Unsigned(index.into()),
+                    &"the index of one of the 24 rotations",
+                )
+            })?;
+            let pos = rot * c.get_position();
+            placed[cube.shell_index(&pos)] = Some(Cubit::with_orientation(pos, index));
+        }
+        // Two cubits turned to the same place leave another place empty.
+        cube.cubits = placed
+            .into_iter()
+            .collect::<Option<_>>()
+            .ok_or_else(|| D::Error::custom("two cubits are turned to the same place"))?;
+        cube.validate().map_err(D::Error::custom)?;
+        Ok(cube)
+    }
+}
+
+/// Write a [`Cube`] as the letters of its stickers, as in [`Facelets`]'s
+/// `Display`, instead of its compact form.
+///
+/// Use it with `#[serde(with = "puzzle_cube::facelet_form")]`. Stickers don't
+/// show how centers are turned or which of the centers of one color is where,
+/// so these may differ on the cube that is read back.
+#[cfg(feature = "serde")]
+pub mod facelet_form {
+    use super::Cube;
+    use serde::{de::Error as _, Deserialize, Deserializer, Serializer};
+
+    /// Write the stickers of a cube.
+    pub fn serialize<S: Serializer>(cube: &Cube, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(&cube.to_facelets())
+    }
+
+    /// Read a cube from its stickers, which must pass [`Cube::validate`].
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Cube, D::Error> {
+        let facelets = String::deserialize(deserializer)?;
+        let count = facelets.chars().filter(|c| !c.is_whitespace()).count();
+        let sides = (1..)
+            .find(|sides| 6 * sides * sides >= count)
+            .expect("some number of sides has enough stickers");
+        let cube = Cube::from_facelets(sides, &facelets).map_err(D::Error::custom)?;
+        cube.validate().map_err(D::Error::custom)?;
+        Ok(cube)
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::Cube;
     use crate::{
-        cubit::Cubit,
+        algorithm::Algorithm,
+        cubit::{rotation_index, Cubit},
         error::Error,
+        facelet::{Color, Face},
         movement::{Layer, Move, MoveType},
+        orientation::Orientation,
+        piece::{PieceId, PieceKind, PieceType, Position},
     };
-    use nalgebra::Vector3;
+    use nalgebra::{Matrix3, Vector3};
 
     #[test]
     fn test_invalid_side() {
@@ -305,4 +895,635 @@ mod test {
         }
         assert_eq!(cube, Cube { sides: 100, cubits })
     }
+
+    #[test]
+    fn test_apply() {
+        let sexy: Algorithm = "R U R' U'".parse().unwrap();
+        let mut cube = Cube::new3x3x3();
+        cube.apply(&sexy).unwrap();
+        assert_ne!(cube, Cube::new3x3x3());
+        cube.apply(&sexy.inverse()).unwrap();
+        assert_eq!(cube, Cube::new3x3x3());
+
+        cube.apply(&(sexy * 6)).unwrap();
+        assert_eq!(cube, Cube::new3x3x3());
+
+        let mut cube = Cube::with_number_sides(5).unwrap();
+        let alg: Algorithm = "3Rw U' 2F2 x M y'".parse().unwrap();
+        cube.apply(&alg).unwrap();
+        for mv in alg.inverse().iter() {
+            cube.rotate(mv).unwrap();
+        }
+        assert_eq!(cube, Cube::with_number_sides(5).unwrap());
+    }
+
+    #[test]
+    fn test_rotate_layers() {
+        let sides = [
+            (Face::Up, Move::rotate_top as fn(Layer, MoveType) -> Move),
+            (Face::Right, Move::rotate_right),
+            (Face::Front, Move::rotate_front),
+            (Face::Down, Move::rotate_bottom),
+            (Face::Left, Move::rotate_left),
+            (Face::Back, Move::rotate_back),
+        ];
+        let types = [
+            MoveType::Clockwise,
+            MoveType::CounterClockwise,
+            MoveType::Twice,
+        ];
+        for n in 2..=6 {
+            let mut cube = Cube::with_number_sides(n).unwrap();
+            cube.apply(&"R U2 F' 2Lw D' 2B x".parse().unwrap()).unwrap();
+            let offset = cube.offset() as isize;
+            let step = cube.step() as isize;
+            for &(face, new) in sides.iter() {
+                let layers = (0..n)
+                    .map(Layer::Single)
+                    .chain((1..=n).map(Layer::Multiple));
+                for layer in layers {
+                    for &move_type in types.iter() {
+                        let mv = new(layer, move_type);
+                        // Turn each cubit in the layers on its own.
+                        let turn = rotation_index(mv.rotation_matrix()).unwrap();
+                        let mut expected = Cube {
+                            sides: n,
+                            cubits: cube.cubits.clone(),
+                        };
+                        for c in cube.cubits.iter() {
+                            let depth =
+                                ((offset - face.normal().dot(&c.get_position())) / step) as usize;
+                            let mut c = *c;
+                            let turned = match layer {
+                                Layer::Single(l) => depth == l,
+                                Layer::Multiple(l) => depth < l,
+                            };
+                            if turned {
+                                c.rotate(turn);
+                            }
+                            let place = expected.shell_index(&c.get_position());
+                            expected.cubits[place] = c;
+                        }
+                        let mut turned = Cube {
+                            sides: n,
+                            cubits: cube.cubits.clone(),
+                        };
+                        turned.rotate(&mv).unwrap();
+                        assert_eq!(turned, expected, "{} on {}x{0}x{0}", mv, n);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_apply_invalid_layer() {
+        let mut cube = Cube::new3x3x3();
+        let alg: Algorithm = "R U 3Rw 4U".parse().unwrap();
+        assert_eq!(cube.apply(&alg), Err(Error::InvalidMoveLayer));
+        assert_eq!(cube, Cube::new3x3x3());
+        assert_eq!(
+            cube.rotate(&Move::rotate_top(Layer::Multiple(4), MoveType::Clockwise)),
+            Err(Error::InvalidMoveLayer)
+        );
+
+        // `M`, `E` and `S` turn the middle layer, which even cubes don't have.
+        let mut cube = Cube::with_number_sides(4).unwrap();
+        assert_eq!(
+            cube.apply(&"S".parse().unwrap()),
+            Err(Error::InvalidMoveLayer)
+        );
+        assert_eq!(cube, Cube::with_number_sides(4).unwrap());
+        for (sides, slices) in [(3, "2L 2D 2F'"), (5, "3L 3D 3F'"), (7, "4L 4D 4F'")].iter() {
+            let mut cube = Cube::with_number_sides(*sides).unwrap();
+            cube.apply(&"M E S'".parse().unwrap()).unwrap();
+            let mut expected = Cube::with_number_sides(*sides).unwrap();
+            expected.apply(&slices.parse().unwrap()).unwrap();
+            assert_eq!(cube, expected);
+        }
+    }
+
+    #[test]
+    fn test_is_solved() {
+        for sides in 2..=5 {
+            let mut cube = Cube::with_number_sides(sides).unwrap();
+            assert!(cube.is_solved());
+            cube.apply(&"x y' z2".parse().unwrap()).unwrap();
+            assert!(cube.is_solved());
+            cube.apply(&"R".parse().unwrap()).unwrap();
+            assert!(!cube.is_solved());
+            cube.apply(&"R' U R U' R' F".parse().unwrap()).unwrap();
+            assert!(!cube.is_solved());
+            cube.apply(&"F' R U R' U'".parse().unwrap()).unwrap();
+            assert!(cube.is_solved());
+        }
+
+        let mut cube = Cube::new3x3x3();
+        cube.apply(&"M2 E2".parse().unwrap()).unwrap();
+        assert!(!cube.is_solved());
+    }
+
+    #[test]
+    fn test_is_solved_centers() {
+        let rot = rotation_index(&Matrix3::new(0, 1, 0, -1, 0, 0, 0, 0, 1)).unwrap();
+
+        // Turning the top center of a 3x3x3 in place can't be seen.
+        let mut cube = Cube::new3x3x3();
+        for c in cube.cubits.iter_mut() {
+            if c.get_position() == Vector3::new(0, 0, 1) {
+                c.rotate(rot);
+            }
+        }
+        assert!(cube.is_solved());
+
+        // Neither can swapping the top centers of a 4x4x4 around.
+        let mut cube = Cube::with_number_sides(4).unwrap();
+        for c in cube.cubits.iter_mut() {
+            let pos = c.get_position();
+            if pos[2] == 3 && pos[0].abs() == 1 && pos[1].abs() == 1 {
+                c.rotate(rot);
+            }
+        }
+        assert!(cube.is_solved());
+
+        // But moving a center to another side can.
+        let mut cube = Cube::with_number_sides(4).unwrap();
+        for c in cube.cubits.iter_mut() {
+            if c.get_position() == Vector3::new(1, 1, 3) {
+                c.rotate(rotation_index(&Matrix3::new(1, 0, 0, 0, 0, 1, 0, -1, 0)).unwrap());
+            }
+        }
+        assert!(!cube.is_solved());
+    }
+
+    #[test]
+    fn test_orientation() {
+        for sides in 2..=5 {
+            let mut cube = Cube::with_number_sides(sides).unwrap();
+            assert_eq!(cube.orientation(), Ok(Orientation::UpFront));
+            cube.apply(&"x".parse().unwrap()).unwrap();
+            assert_eq!(cube.orientation(), Ok(Orientation::FrontDown));
+            cube.apply(&"y".parse().unwrap()).unwrap();
+            assert_eq!(cube.orientation(), Ok(Orientation::FrontRight));
+            for &o in Orientation::ALL.iter() {
+                let mut cube = Cube::with_number_sides(sides).unwrap();
+                cube.apply(&o.to_algorithm()).unwrap();
+                assert_eq!(cube.orientation(), Ok(o));
+                // Turning the sides keeps the centers where they are, and
+                // leaving the D, L and B sides keeps the corner at DBL.
+                let mut cube = Cube::with_number_sides(sides).unwrap();
+                cube.apply(&"U R2 F' R U' F2".parse().unwrap()).unwrap();
+                cube.apply(&o.to_algorithm()).unwrap();
+                assert_eq!(cube.orientation(), Ok(o));
+            }
+        }
+        // Turning the down side of an even cube turns the corner at DBL, like
+        // turning the top and then the whole cube.
+        for sides in [2, 4].iter() {
+            let mut cube = Cube::with_number_sides(*sides).unwrap();
+            cube.apply(&"D".parse().unwrap()).unwrap();
+            let mut held = Cube::with_number_sides(*sides).unwrap();
+            held.apply(&"U y'".parse().unwrap()).unwrap();
+            assert_eq!(cube.orientation(), held.orientation());
+            assert_ne!(cube.orientation(), Ok(Orientation::UpFront));
+        }
+
+        let mut cube = Cube::new3x3x3();
+        cube.apply(&"M".parse().unwrap()).unwrap();
+        assert_eq!(cube.orientation(), Ok(Orientation::BackUp));
+        // A second front center on top.
+        let rot = rotation_index(&Matrix3::new(0, 0, -1, 0, 1, 0, 1, 0, 0)).unwrap();
+        let top = Vector3::new(0, 0, 1);
+        let i = cube.shell_index(&top);
+        cube.cubits[i] = Cubit::with_orientation(top, rot);
+        assert_eq!(cube.orientation(), Err(Error::InvalidCenters));
+    }
+
+    #[test]
+    fn test_pieces() {
+        let mut cube = Cube::new3x3x3();
+        assert_eq!(cube.pieces().count(), 26);
+        assert_eq!(cube.pieces_of(PieceType::Corner).count(), 8);
+        assert_eq!(cube.pieces_of(PieceType::Edge).count(), 12);
+        assert_eq!(cube.pieces_of(PieceType::Center).count(), 6);
+        let ufr = Position::new(2, 2, 2);
+        let piece = cube.piece_at(ufr).unwrap();
+        assert_eq!(piece.id(), PieceId::new(ufr));
+        assert_eq!(piece.kind(), PieceKind::Corner);
+        assert_eq!(piece.piece_type(), PieceType::Corner);
+        assert_eq!(piece.orientation(), Orientation::UpFront);
+
+        cube.apply(&"R".parse().unwrap()).unwrap();
+        assert_eq!(
+            cube.location_of(PieceId::new(ufr)),
+            Some(Position::new(0, 2, 2))
+        );
+        let piece = cube.piece_at(ufr).unwrap();
+        assert_eq!(piece.id().home(), Position::new(2, 2, 0));
+        assert_eq!(piece.orientation(), Orientation::FrontDown);
+        assert_eq!(
+            cube.piece_at(Position::new(0, 1, 1)).unwrap().orientation(),
+            Orientation::UpFront
+        );
+
+        assert_eq!(cube.piece_at(Position::new(1, 1, 1)), None);
+        assert_eq!(cube.piece_at(Position::new(3, 0, 0)), None);
+        assert_eq!(cube.location_of(PieceId::new(Position::new(1, 1, 1))), None);
+
+        for sides in 2..=6 {
+            let mut cube = Cube::with_number_sides(sides).unwrap();
+            cube.apply(&"R U2 F' Rw' x B L2 y 2D z'".parse().unwrap())
+                .unwrap();
+            assert_eq!(cube.pieces_of(PieceType::Edge).count(), 12 * (sides - 2));
+            for piece in cube.pieces() {
+                assert_eq!(cube.location_of(piece.id()), Some(piece.position()));
+                assert_eq!(cube.piece_at(piece.position()), Some(piece));
+            }
+        }
+    }
+
+    #[test]
+    fn test_orbits() {
+        let mut cube = Cube::with_number_sides(7).unwrap();
+        let orbits = cube.orbits().unwrap();
+        let kinds: Vec<_> = orbits.iter().map(|o| o.kind()).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                PieceKind::Corner,
+                PieceKind::Midge,
+                PieceKind::Wing(1),
+                PieceKind::Wing(2),
+                PieceKind::Center,
+                PieceKind::XCenter(1),
+                PieceKind::XCenter(2),
+                PieceKind::PlusCenter(1),
+                PieceKind::PlusCenter(2),
+                PieceKind::Oblique(1, 2),
+                PieceKind::Oblique(2, 1),
+            ]
+        );
+        assert_eq!(orbits.iter().map(|o| o.pieces()).sum::<usize>(), 218);
+        assert!(orbits.iter().all(|o| o.is_solved()));
+
+        // Turning a slice leaves a wing orbit unpaired and swapped.
+        cube.apply(&"x' 2R".parse().unwrap()).unwrap();
+        for orbit in cube.orbits().unwrap() {
+            match orbit.kind() {
+                PieceKind::Wing(1) => {
+                    assert_eq!(orbit.solved(), 20);
+                    assert_eq!(orbit.is_odd(), Some(true));
+                }
+                PieceKind::XCenter(1) | PieceKind::PlusCenter(1) | PieceKind::Oblique(..) => {
+                    assert!(!orbit.is_solved());
+                    assert_eq!(orbit.is_odd(), None);
+                }
+                _ => {
+                    assert!(orbit.is_solved(), "{:?}", orbit);
+                    assert_ne!(orbit.is_odd(), Some(true));
+                }
+            }
+        }
+
+        // Held the way the bottom is turned, the bottom corners are solved and
+        // the top ones are cycled.
+        let mut cube = Cube::with_number_sides(4).unwrap();
+        cube.apply(&"D".parse().unwrap()).unwrap();
+        let corners = cube.orbits().unwrap()[0];
+        assert_eq!(corners.solved(), 4);
+        assert_eq!(corners.is_odd(), Some(true));
+
+        let mut cube = Cube::with_number_sides(4).unwrap();
+        cube.apply(&"R U R' U' y".parse().unwrap()).unwrap();
+        let corners = cube.orbits().unwrap()[0];
+        assert_eq!(corners.kind(), PieceKind::Corner);
+        assert_eq!(corners.solved(), 4);
+        assert_eq!(corners.is_odd(), Some(false));
+    }
+
+    #[test]
+    fn test_shell_index() {
+        for sides in 2..=7 {
+            let mut cube = Cube::with_number_sides(sides).unwrap();
+            for (i, c) in cube.cubits.iter().enumerate() {
+                assert_eq!(cube.shell_index(&c.get_position()), i);
+            }
+            // Moves keep every cubit at the index of its place.
+            cube.apply(&"R U2 F' Rw' x B L2 y 2D z'".parse().unwrap())
+                .unwrap();
+            for (i, c) in cube.cubits.iter().enumerate() {
+                assert_eq!(cube.shell_index(&c.get_position()), i);
+            }
+        }
+    }
+
+    #[test]
+    fn test_to_facelets() {
+        for sides in 2..=6 {
+            let facelets = Cube::with_number_sides(sides).unwrap().to_facelets();
+            assert_eq!(facelets.sides(), sides);
+            assert_eq!(facelets.stickers().len(), 6 * sides * sides);
+            for face in Face::ALL.iter() {
+                assert!(facelets.face(*face).iter().all(|&c| c == face.color()));
+            }
+        }
+
+        let mut cube = Cube::new2x2x2();
+        cube.rotate(&Move::rotate_right(Layer::Single(0), MoveType::Clockwise))
+            .unwrap();
+        let facelets = cube.to_facelets();
+        assert_eq!(facelets.to_string(), "UFUFRRRRFDFDDBDBLLLLUBUB");
+        assert_eq!(facelets.get(Face::Up, 0, 1), Color::Blue);
+        assert_eq!(facelets.get(Face::Back, 1, 0), Color::Yellow);
+    }
+
+    #[test]
+    fn test_to_kociemba_string() {
+        let mut cube = Cube::new3x3x3();
+        assert_eq!(
+            cube.to_kociemba_string().unwrap(),
+            "UUUUUUUUURRRRRRRRRFFFFFFFFFDDDDDDDDDLLLLLLLLLBBBBBBBBB"
+        );
+        cube.apply(&"y x'".parse().unwrap()).unwrap();
+        assert_eq!(
+            cube.to_kociemba_string().unwrap(),
+            "UUUUUUUUURRRRRRRRRFFFFFFFFFDDDDDDDDDLLLLLLLLLBBBBBBBBB"
+        );
+
+        let mut cube = Cube::new3x3x3();
+        cube.apply(&"U".parse().unwrap()).unwrap();
+        assert_eq!(
+            cube.to_kociemba_string().unwrap(),
+            "UUUUUUUUUBBBRRRRRRRRRFFFFFFDDDDDDDDDFFFLLLLLLLLLBBBBBB"
+        );
+
+        let mut cube = Cube::new3x3x3();
+        cube.apply(&"R".parse().unwrap()).unwrap();
+        assert_eq!(
+            cube.to_kociemba_string().unwrap(),
+            "UUFUUFUUFRRRRRRRRRFFDFFDFFDDDBDDBDDBLLLLLLLLLUBBUBBUBB"
+        );
+
+        assert_eq!(
+            Cube::new2x2x2().to_kociemba_string(),
+            Err(Error::UnsupportedSides {
+                expected: 3,
+                found: 2
+            })
+        );
+    }
+
+    #[test]
+    fn test_from_facelets() {
+        for sides in 2..=6 {
+            let mut cube = Cube::with_number_sides(sides).unwrap();
+            let solved = cube.to_facelets().to_string();
+            assert_eq!(Cube::from_facelets(sides, &solved).unwrap(), cube);
+
+            cube.apply(&"R U2 F' Rw' x 2D B L2 y".parse().unwrap())
+                .unwrap();
+            let facelets = cube.to_facelets().to_string();
+            let from = Cube::from_facelets(sides, &facelets).unwrap();
+            assert_eq!(from.to_facelets().to_string(), facelets);
+            // Centers can be turned in any way without changing the stickers.
+            if sides == 2 {
+                assert_eq!(from, cube);
+            }
+        }
+
+        let cube = Cube::from_facelets(2, "UFUF RRRR FDFD DBDB LLLL UBUB").unwrap();
+        let mut turned = Cube::new2x2x2();
+        turned.apply(&"R".parse().unwrap()).unwrap();
+        assert_eq!(cube, turned);
+
+        let cube = Cube::from_facelets(
+            3,
+            "UUFUUFUUF RRRRRRRRR FFDFFDFFD DDBDDBDDB LLLLLLLLL UBBUBBUBB",
+        )
+        .unwrap();
+        assert!(!cube.is_solved());
+        assert_eq!(
+            cube.to_kociemba_string().unwrap(),
+            "UUFUUFUUFRRRRRRRRRFFDFFDFFDDDBDDBDDBLLLLLLLLLUBBUBBUBB"
+        );
+    }
+
+    #[test]
+    fn test_from_facelets_invalid() {
+        let solved = Cube::new3x3x3().to_facelets().to_string();
+        let with = |i: usize, letter: &str| {
+            let mut facelets = solved.clone();
+            facelets.replace_range(i..i + letter.len(), letter);
+            Cube::from_facelets(3, &facelets)
+        };
+
+        assert_eq!(
+            Cube::from_facelets(1, &solved),
+            Err(Error::InvalidNumberSides(1))
+        );
+        assert_eq!(
+            Cube::from_facelets(3, &solved[1..]),
+            Err(Error::InvalidFaceletCount {
+                expected: 54,
+                found: 53
+            })
+        );
+        assert_eq!(
+            with(10, "X"),
+            Err(Error::InvalidFacelet {
+                index: 10,
+                found: 'X'
+            })
+        );
+        assert_eq!(
+            with(0, "R"),
+            Err(Error::InvalidColorCount {
+                color: Color::Yellow,
+                expected: 9,
+                found: 8
+            })
+        );
+        // Swapping two stickers of a corner makes a mirror image of it.
+        assert_eq!(with(8, "RU"), Err(Error::InvalidPiece { facelet: 8 }));
+        // An edge can't have the same color twice.
+        let mut facelets = solved.clone();
+        facelets.replace_range(7..8, "F");
+        facelets.replace_range(25..26, "U");
+        assert_eq!(
+            Cube::from_facelets(3, &facelets),
+            Err(Error::InvalidPiece { facelet: 25 })
+        );
+        // The up-front edge at down-front and the down-back edge at up-back.
+        let mut facelets = solved;
+        facelets.replace_range(28..29, "U");
+        facelets.replace_range(1..2, "D");
+        assert_eq!(
+            Cube::from_facelets(3, &facelets),
+            Err(Error::DuplicatePiece { facelet: 1 })
+        );
+    }
+
+    #[test]
+    fn test_validate() {
+        for sides in 2..=6 {
+            let mut cube = Cube::with_number_sides(sides).unwrap();
+            assert_eq!(cube.validate(), Ok(()));
+            cube.apply(&"R U2 F' Rw' x B L2 y".parse().unwrap())
+                .unwrap();
+            assert_eq!(cube.validate(), Ok(()));
+        }
+
+        let solved = Cube::new3x3x3().to_facelets().to_string();
+        let with = |stickers: &[(usize, &str)]| {
+            let mut facelets = solved.clone();
+            for &(i, letter) in stickers {
+                facelets.replace_range(i..i + 1, letter);
+            }
+            Cube::from_facelets(3, &facelets).unwrap().validate()
+        };
+        // The stickers of the up-right-front corner are at 8, 9 and 20.
+        assert_eq!(
+            with(&[(8, "F"), (9, "U"), (20, "R")]),
+            Err(Error::TwistedCorner {
+                corner: "URF",
+                clockwise: true
+            })
+        );
+        assert_eq!(
+            with(&[(8, "R"), (9, "F"), (20, "U")]),
+            Err(Error::TwistedCorner {
+                corner: "URF",
+                clockwise: false
+            })
+        );
+        // The stickers of the up-front edge are at 7 and 19, up-right at 5 and 10.
+        assert_eq!(
+            with(&[(7, "F"), (19, "U")]),
+            Err(Error::FlippedEdge { edge: "UF" })
+        );
+        assert_eq!(
+            with(&[(10, "F"), (19, "R")]),
+            Err(Error::SwappedPieces {
+                first: "UR",
+                second: "UF"
+            })
+        );
+    }
+
+    #[test]
+    fn test_validate_pieces() {
+        let corrupt = |change: &dyn Fn(&mut Vec<Cubit>)| {
+            let mut cube = Cube::new3x3x3();
+            change(&mut cube.cubits);
+            cube.validate()
+        };
+        // The first cubit is the down-back-left corner, whose down sticker is 33.
+        let dbl = Vector3::new(-1, -1, -1);
+        assert_eq!(
+            corrupt(&|c| c[0] = Cubit::std_from_position(Vector3::new(-2, -1, -1))),
+            Err(Error::InvalidPosition {
+                position: [-2, -1, -1]
+            })
+        );
+        assert_eq!(
+            corrupt(&|c| c[0] = Cubit::std_from_position(Vector3::new(0, 0, 0))),
+            Err(Error::InvalidPosition {
+                position: [0, 0, 0]
+            })
+        );
+        assert_eq!(
+            corrupt(&|c| c[1] = Cubit::std_from_position(dbl)),
+            Err(Error::OverlappingPieces { facelet: 33 })
+        );
+        // The last cubit is the up-right-front corner.
+        assert_eq!(
+            corrupt(&|c| {
+                c.pop();
+            }),
+            Err(Error::MissingPiece { facelet: 8 })
+        );
+        // A second down-back-right corner turned to the down-back-left place,
+        // the one at its own place is found next and has its right sticker at 17.
+        let quarter = rotation_index(&Matrix3::new(0, -1, 0, 1, 0, 0, 0, 0, 1)).unwrap();
+        assert_eq!(
+            corrupt(&|c| c[0] = Cubit::with_orientation(dbl, quarter)),
+            Err(Error::DuplicatePiece { facelet: 17 })
+        );
+        // The up and down centers swapped by a half turn about the front.
+        let half = rotation_index(&Matrix3::new(1, 0, 0, 0, -1, 0, 0, 0, -1)).unwrap();
+        assert_eq!(
+            corrupt(&|c| {
+                let (up, down) = (Vector3::new(0, 0, 1), Vector3::new(0, 0, -1));
+                for c in c.iter_mut() {
+                    if c.get_position() == up {
+                        *c = Cubit::with_orientation(down, half);
+                    } else if c.get_position() == down {
+                        *c = Cubit::with_orientation(up, half);
+                    }
+                }
+            }),
+            Err(Error::InvalidCenters)
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde() {
+        let mut cube = Cube::new2x2x2();
+        assert_eq!(
+            serde_json::to_string(&cube).unwrap(),
+            r#"{"sides":2,"orientations":[0,0,0,0,0,0,0,0]}"#
+        );
+        for sides in 2..=5 {
+            let mut cube = Cube::with_number_sides(sides).unwrap();
+            cube.apply(&"R U2 F' Rw' x B L2 y".parse().unwrap())
+                .unwrap();
+            let json = serde_json::to_string(&cube).unwrap();
+            assert_eq!(serde_json::from_str::<Cube>(&json).unwrap(), cube);
+        }
+
+        cube.apply(&"R".parse().unwrap()).unwrap();
+        let json = r#"{"sides":2,"orientations":[0,22,0,22,0,22,0,22]}"#;
+        assert_eq!(serde_json::to_string(&cube).unwrap(), json);
+        let invalid = |orientations: &str| {
+            let json = format!(r#"{{"sides":2,"orientations":{}}}"#, orientations);
+            serde_json::from_str::<Cube>(&json).is_err()
+        };
+        assert!(invalid("[0,22,0,22,0,22,0]"));
+        assert!(invalid("[0,22,0,22,0,22,0,24]"));
+        // The rotations are listed by where the cubits are on a solved cube.
+        let mut turned = Cube::new2x2x2();
+        turned.apply(&"R U".parse().unwrap()).unwrap();
+        let json = r#"{"sides":2,"orientations":[0,22,0,16,8,22,8,16]}"#;
+        assert_eq!(serde_json::to_string(&turned).unwrap(), json);
+        assert_eq!(serde_json::from_str::<Cube>(json).unwrap(), turned);
+        let mut turned = Cube::new3x3x3();
+        turned.apply(&"R U F'".parse().unwrap()).unwrap();
+        let json = concat!(
+            r#"{"sides":3,"orientations":"#,
+            "[0,0,14,0,0,14,6,6,8,0,0,22,0,22,6,6,8,8,8,22,8,8,22,8,8,8]}",
+        );
+        assert_eq!(serde_json::to_string(&turned).unwrap(), json);
+        assert_eq!(serde_json::from_str::<Cube>(json).unwrap(), turned);
+        // Turning only one corner makes it the same piece as another one.
+        assert!(invalid("[22,0,0,0,0,0,0,0]"));
+
+        #[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+        struct Saved {
+            #[serde(with = "super::facelet_form")]
+            cube: Cube,
+        }
+        let saved = Saved { cube };
+        let json = serde_json::to_string(&saved).unwrap();
+        assert_eq!(json, r#"{"cube":"UFUFRRRRFDFDDBDBLLLLUBUB"}"#);
+        assert_eq!(serde_json::from_str::<Saved>(&json).unwrap(), saved);
+        assert!(serde_json::from_str::<Saved>(r#"{"cube":"UFUF"}"#).is_err());
+        // A single twisted corner can't be solved.
+        let twisted = concat!(
+            r#"{"cube":""#,
+            "UUUUUUUUF URRRRRRRR FFRFFFFFF DDDDDDDDD LLLLLLLLL BBBBBBBBB",
+            r#""}"#
+        );
+        assert!(serde_json::from_str::<Saved>(twisted).is_err());
+    }
 }
@@ -0,0 +1,1036 @@
+use crate::{
+    algorithm::Algorithm,
+    cube::Cube,
+    error::Error,
+    facelet::Face,
+    movement::{AxisInner, Layer, LayerInner, Move, MoveType},
+};
+use rand::{seq::SliceRandom, Rng};
+use std::{convert::TryFrom, ops::Mul, sync::OnceLock};
+
+// Pieces are numbered as in Kociemba's solver, see `CubieCube`, each with its
+// stickers listed clockwise starting from the up or down side, or the front or
+// back side for the middle edges.
+
+/// The names of the corner places.
+pub(crate) const CORNER_NAMES: [&str; 8] = ["URF", "UFL", "ULB", "UBR", "DFR", "DLF", "DBL", "DRB"];
+
+/// The names of the edge places.
+pub(crate) const EDGE_NAMES: [&str; 12] = [
+    "UR", "UF", "UL", "UB", "DR", "DF", "DL", "DB", "FR", "FL", "BL", "BR",
+];
+
+/// The sides of the stickers of each corner.
+const CORNER_COLORS: [[Face; 3]; 8] = [
+    [Face::Up, Face::Right, Face::Front],
+    [Face::Up, Face::Front, Face::Left],
+    [Face::Up, Face::Left, Face::Back],
+    [Face::Up, Face::Back, Face::Right],
+    [Face::Down, Face::Front, Face::Right],
+    [Face::Down, Face::Left, Face::Front],
+    [Face::Down, Face::Back, Face::Left],
+    [Face::Down, Face::Right, Face::Back],
+];
+
+/// The facelets of a 3x3x3 showing the stickers of the corner at each place.
+const CORNER_FACELETS: [[usize; 3]; 8] = [
+    [8, 9, 20],
+    [6, 18, 38],
+    [0, 36, 47],
+    [2, 45, 11],
+    [29, 26, 15],
+    [27, 44, 24],
+    [33, 53, 42],
+    [35, 17, 51],
+];
+
+/// The sides of the stickers of each edge.
+const EDGE_COLORS: [[Face; 2]; 12] = [
+    [Face::Up, Face::Right],
+    [Face::Up, Face::Front],
+    [Face::Up, Face::Left],
+    [Face::Up, Face::Back],
+    [Face::Down, Face::Right],
+    [Face::Down, Face::Front],
+    [Face::Down, Face::Left],
+    [Face::Down, Face::Back],
+    [Face::Front, Face::Right],
+    [Face::Front, Face::Left],
+    [Face::Back, Face::Left],
+    [Face::Back, Face::Right],
+];
+
+/// The facelets of a 3x3x3 showing the stickers of the edge at each place.
+const EDGE_FACELETS: [[usize; 2]; 12] = [
+    [5, 10],
+    [7, 19],
+    [3, 37],
+    [1, 46],
+    [32, 16],
+    [28, 25],
+    [30, 43],
+    [34, 52],
+    [23, 12],
+    [21, 41],
+    [50, 39],
+    [48, 14],
+];
+
+/// The index of the down back left corner, which never moves on a 2x2x2 with
+/// only up, right and front turns.
+pub(crate) const DBL: usize = 6;
+
+/// The number of face turns, a quarter turn clockwise, a half turn and a
+/// quarter turn counter-clockwise of each side in the order of [`Face::ALL`].
+///
+/// Face turn `m` turns side `m / 3` by `m % 3 + 1` clockwise quarter turns.
+pub(crate) const FACE_TURNS: usize = 18;
+
+/// The axis of each side in the order of [`Face::ALL`].
+const FACE_AXES: [AxisInner; 6] = [
+    AxisInner::Z,
+    AxisInner::Y,
+    AxisInner::X,
+    AxisInner::NegZ,
+    AxisInner::NegY,
+    AxisInner::NegX,
+];
+
+/// A move on a 3x3x3 as up to two face turns and then a number of clockwise
+/// quarter turns of the whole cube about a side.
+struct CubieMove {
+    turns: [Option<usize>; 2],
+    rotation: Option<(Face, u8)>,
+}
+
+/// The face turns and whole cube rotation doing the same as a move on a
+/// 3x3x3. Slices and wide turns are the outer layers they leave turning the
+/// other way and then the whole cube.
+fn cubie_move(mv: &Move) -> Result<CubieMove, Error> {
+    let face = Face::ALL[FACE_AXES
+        .iter()
+        .position(|&axis| axis == mv.axis)
+        .expect("every axis has a side")];
+    let opposite = Face::ALL[(face as usize + 3) % 6];
+    let quarters = mv.move_type.quarter_turns();
+    let turn = |side: Face, quarters: u8| Some(3 * side as usize + quarters as usize - 1);
+    let (turns, rotation) = match mv.affected_range.for_sides(3)? {
+        LayerInner::Multiple(0) => ([None, None], None),
+        LayerInner::Single(0) | LayerInner::Multiple(1) => ([turn(face, quarters), None], None),
+        // The far layer is the opposite side turning the other way.
+        LayerInner::Single(2) => ([turn(opposite, 4 - quarters), None], None),
+        LayerInner::Single(1) => (
+            [turn(face, 4 - quarters), turn(opposite, quarters)],
+            Some((face, quarters)),
+        ),
+        LayerInner::Multiple(2) => ([turn(opposite, quarters), None], Some((face, quarters))),
+        LayerInner::Multiple(3) | LayerInner::WholeCube => ([None, None], Some((face, quarters))),
+        _ => return Err(Error::InvalidMoveLayer),
+    };
+    Ok(CubieMove { turns, rotation })
+}
+
+/// A clockwise quarter turn of the whole cube about each side in the order of
+/// [`Face::ALL`], with the pieces named after the places they come from
+/// rather than after the centers, made the first time it is needed.
+pub(crate) fn rotation_table() -> &'static [CubieCube; 6] {
+    static TABLE: OnceLock<[CubieCube; 6]> = OnceLock::new();
+    TABLE.get_or_init(|| {
+        let solved = Cube::new3x3x3().to_facelets();
+        let home = |color| {
+            *Face::ALL
+                .iter()
+                .find(|&&face| solved.get(face, 1, 1) == color)
+                .expect("every color has a center")
+        };
+        let mut table = [CubieCube::SOLVED; 6];
+        for (cube, &axis) in table.iter_mut().zip(FACE_AXES.iter()) {
+            let mut rotated = Cube::new3x3x3();
+            rotated
+                .rotate(&Move {
+                    move_type: MoveType::Clockwise,
+                    axis,
+                    affected_range: LayerInner::WholeCube,
+                })
+                .expect("any cube can be turned whole");
+            let mut faces = [Face::Up; 54];
+            for (face, &color) in faces.iter_mut().zip(rotated.to_facelets().stickers()) {
+                *face = home(color);
+            }
+            *cube = CubieCube::from_faces(&faces).expect("rotating keeps the pieces");
+        }
+        table
+    })
+}
+
+/// The move doing a face turn.
+pub(crate) fn face_turn(m: usize) -> Move {
+    let layer = Layer::Single(0);
+    let move_type =
+        MoveType::from_quarter_turns((m % 3 + 1) as u8).expect("face turns always turn");
+    match Face::ALL[m / 3] {
+        Face::Up => Move::rotate_top(layer, move_type),
+        Face::Right => Move::rotate_right(layer, move_type),
+        Face::Front => Move::rotate_front(layer, move_type),
+        Face::Down => Move::rotate_bottom(layer, move_type),
+        Face::Left => Move::rotate_left(layer, move_type),
+        Face::Back => Move::rotate_back(layer, move_type),
+    }
+}
+
+/// A 3x3x3 described by which corner and edge is at each place and how it is
+/// twisted or flipped there.
+///
+/// Pieces and places are numbered as in Kociemba's solver. Corners are `URF`,
+/// `UFL`, `ULB`, `UBR`, `DFR`, `DLF`, `DBL` and `DRB`, edges are `UR`, `UF`,
+/// `UL`, `UB`, `DR`, `DF`, `DL`, `DB`, `FR`, `FL`, `BL` and `BR`. A corner is
+/// twisted `1` when its up or down sticker is turned clockwise from the up or
+/// down side, an edge is flipped `1` when its up or down sticker (front or back
+/// for the middle layer edges) isn't on that side.
+///
+/// The way the cube is held is not part of a cubie cube, pieces are always
+/// named after the centers they match. So a face turn is a single
+/// multiplication by one of the 18 face turns, which are worked out once, and
+/// moves that turn the centers also rename the pieces after them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CubieCube {
+    pub(crate) cp: [u8; 8],
+    pub(crate) co: [u8; 8],
+    pub(crate) ep: [u8; 12],
+    pub(crate) eo: [u8; 12],
+}
+
+/// A clockwise quarter turn of each side in the order of [`Face::ALL`].
+const QUARTER_TURNS: [CubieCube; 6] = [
+    CubieCube {
+        cp: [3, 0, 1, 2, 4, 5, 6, 7],
+        co: [0; 8],
+        ep: [3, 0, 1, 2, 4, 5, 6, 7, 8, 9, 10, 11],
+        eo: [0; 12],
+    },
+    CubieCube {
+        cp: [4, 1, 2, 0, 7, 5, 6, 3],
+        co: [2, 0, 0, 1, 1, 0, 0, 2],
+        ep: [8, 1, 2, 3, 11, 5, 6, 7, 4, 9, 10, 0],
+        eo: [0; 12],
+    },
+    CubieCube {
+        cp: [1, 5, 2, 3, 0, 4, 6, 7],
+        co: [1, 2, 0, 0, 2, 1, 0, 0],
+        ep: [0, 9, 2, 3, 4, 8, 6, 7, 1, 5, 10, 11],
+        eo: [0, 1, 0, 0, 0, 1, 0, 0, 1, 1, 0, 0],
+    },
+    CubieCube {
+        cp: [0, 1, 2, 3, 5, 6, 7, 4],
+        co: [0; 8],
+        ep: [0, 1, 2, 3, 5, 6, 7, 4, 8, 9, 10, 11],
+        eo: [0; 12],
+    },
+    CubieCube {
+        cp: [0, 2, 6, 3, 4, 1, 5, 7],
+        co: [0, 1, 2, 0, 0, 2, 1, 0],
+        ep: [0, 1, 10, 3, 4, 5, 9, 7, 8, 2, 6, 11],
+        eo: [0; 12],
+    },
+    CubieCube {
+        cp: [0, 1, 3, 7, 4, 5, 2, 6],
+        co: [0, 0, 1, 2, 0, 0, 2, 1],
+        ep: [0, 1, 2, 11, 4, 5, 6, 10, 8, 9, 3, 7],
+        eo: [0, 0, 0, 1, 0, 0, 0, 1, 0, 0, 1, 1],
+    },
+];
+
+/// Every face turn, see [`FACE_TURNS`], made the first time it is needed.
+fn turn_table() -> &'static [CubieCube; FACE_TURNS] {
+    static TABLE: OnceLock<[CubieCube; FACE_TURNS]> = OnceLock::new();
+    TABLE.get_or_init(|| {
+        let mut table = [CubieCube::SOLVED; FACE_TURNS];
+        for (m, cube) in table.iter_mut().enumerate() {
+            for _ in 0..=m % 3 {
+                *cube = cube.multiply(&QUARTER_TURNS[m / 3]);
+            }
+        }
+        table
+    })
+}
+
+impl CubieCube {
+    /// Every piece in its own place.
+    pub const SOLVED: CubieCube = CubieCube {
+        cp: [0, 1, 2, 3, 4, 5, 6, 7],
+        co: [0; 8],
+        ep: [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11],
+        eo: [0; 12],
+    };
+
+    /// Read the pieces of a 3x3x3 as it is held.
+    ///
+    /// Pieces are named after the centers of the colors on them, as in
+    /// [`Cube::to_kociemba_string`], and places after the side they are on.
+    /// So a solved cube is always read as solved, and the same moves on the
+    /// cube and the cubie cube keep them showing the same thing. How the cube
+    /// is held is not kept, see [`CubieCube::to_cube`].
+    ///
+    /// Returns [`Error::InvalidCenters`] if the centers are not arranged like
+    /// on a real cube and [`Error::InvalidPiece`] for a piece whose colors
+    /// don't exist.
+    pub fn from_cube(cube: &Cube) -> Result<Self, Error> {
+        cube.orientation()?;
+        let mut faces = [Face::Up; 54];
+        for (face, letter) in faces.iter_mut().zip(cube.to_kociemba_string()?.chars()) {
+            *face = Face::from_letter(letter).expect("kociemba strings only hold sides");
+        }
+        Self::from_faces(&faces)
+    }
+
+    /// The cube with the same stickers, held with the up side on top and the
+    /// front side at the front.
+    ///
+    /// This is the cube read by [`CubieCube::from_cube`] only if it was held
+    /// that way. Otherwise it is the same cube turned whole, with the same
+    /// [`Cube::to_kociemba_string`] but a different [`Cube::orientation`].
+    pub fn to_cube(self) -> Cube {
+        self.to_cube_with_sides(3)
+    }
+
+    /// Which corner is at each place.
+    pub fn corner_permutation(&self) -> &[u8; 8] {
+        &self.cp
+    }
+
+    /// How the corner at each place is twisted, `0`, `1` or `2` clockwise.
+    pub fn corner_orientation(&self) -> &[u8; 8] {
+        &self.co
+    }
+
+    /// Which edge is at each place.
+    pub fn edge_permutation(&self) -> &[u8; 12] {
+        &self.ep
+    }
+
+    /// Whether the edge at each place is flipped.
+    pub fn edge_orientation(&self) -> &[u8; 12] {
+        &self.eo
+    }
+
+    /// Whether every piece is in its own place.
+    pub fn is_solved(&self) -> bool {
+        *self == Self::SOLVED
+    }
+
+    /// Whether the cube can be solved by turning the sides.
+    ///
+    /// Twists must add up to a multiple of three, flips to a multiple of two
+    /// and the corner and edge permutations must be either both even or both
+    /// odd.
+    pub fn is_solvable(&self) -> bool {
+        self.co.iter().sum::<u8>() % 3 == 0
+            && self.eo.iter().sum::<u8>() % 2 == 0
+            && is_odd(&self.cp) == is_odd(&self.ep)
+    }
+
+    /// Like [`CubieCube::is_solvable`] but names a piece that could be turned
+    /// or two that could be swapped to make the cube solvable.
+    pub(crate) fn check_solvable(&self) -> Result<(), Error> {
+        let twist = self.co.iter().sum::<u8>() % 3;
+        if twist != 0 {
+            // Twisting back a corner twisted by the total fixes it, any other
+            // twisted corner can be twisted further.
+            let at = self
+                .co
+                .iter()
+                .position(|&o| o == twist)
+                .or_else(|| self.co.iter().position(|&o| o != 0))
+                .expect("a corner is twisted");
+            return Err(Error::TwistedCorner {
+                corner: CORNER_NAMES[at],
+                clockwise: self.co[at] == 1,
+            });
+        }
+        if self.eo.iter().sum::<u8>() % 2 != 0 {
+            let at = self
+                .eo
+                .iter()
+                .position(|&o| o == 1)
+                .expect("an edge is flipped");
+            return Err(Error::FlippedEdge {
+                edge: EDGE_NAMES[at],
+            });
+        }
+        if is_odd(&self.cp) != is_odd(&self.ep) {
+            // Putting either piece back in its own place fixes the parity.
+            let (first, second) = match (0..12).find(|&i| self.ep[i] as usize != i) {
+                Some(i) => (EDGE_NAMES[i], EDGE_NAMES[self.ep[i] as usize]),
+                None => {
+                    let i = (0..8)
+                        .find(|&i| self.cp[i] as usize != i)
+                        .expect("the corners are odd");
+                    (CORNER_NAMES[i], CORNER_NAMES[self.cp[i] as usize])
+                }
+            };
+            return Err(Error::SwappedPieces { first, second });
+        }
+        Ok(())
+    }
+
+    /// A uniformly random state that can be reached by turning the sides, see
+    /// [`CubieCube::is_solvable`].
+    pub(crate) fn random<R: Rng>(rng: &mut R) -> Self {
+        let mut cube = Self::SOLVED;
+        cube.cp.shuffle(rng);
+        cube.ep.shuffle(rng);
+        if is_odd(&cube.cp) != is_odd(&cube.ep) {
+            cube.ep.swap(0, 1);
+        }
+        random_orientation(&mut cube.co, 3, rng);
+        random_orientation(&mut cube.eo, 2, rng);
+        cube
+    }
+
+    /// A uniformly random state of a 2x2x2 held with the down back left corner
+    /// in its place, so each state is only made once instead of in each of the
+    /// 24 ways of holding the cube. Edges are left solved.
+    pub(crate) fn random_corners<R: Rng>(rng: &mut R) -> Self {
+        let mut cube = Self::random(rng);
+        let at = cube.cp.iter().position(|&c| c as usize == DBL).unwrap();
+        cube.cp.swap(at, DBL);
+        // The other twists are still uniformly random, only the last one
+        // follows from them.
+        cube.co[DBL] = 0;
+        cube.co[7] = 0;
+        cube.co[7] = (3 - cube.co.iter().sum::<u8>() % 3) % 3;
+        cube.ep = Self::SOLVED.ep;
+        cube.eo = Self::SOLVED.eo;
+        cube
+    }
+
+    /// The state reached by doing `self` and then `other`, both starting from
+    /// a solved cube.
+    pub fn multiply(&self, other: &CubieCube) -> CubieCube {
+        let mut cube = Self::SOLVED;
+        for i in 0..8 {
+            let from = other.cp[i] as usize;
+            cube.cp[i] = self.cp[from];
+            cube.co[i] = (self.co[from] + other.co[i]) % 3;
+        }
+        for i in 0..12 {
+            let from = other.ep[i] as usize;
+            cube.ep[i] = self.ep[from];
+            cube.eo[i] = (self.eo[from] + other.eo[i]) % 2;
+        }
+        cube
+    }
+
+    /// The state that undoes this one, so multiplying them either way round
+    /// is solved.
+    pub fn inverse(&self) -> CubieCube {
+        let mut cube = Self::SOLVED;
+        for i in 0..8 {
+            let to = self.cp[i] as usize;
+            cube.cp[to] = i as u8;
+            cube.co[to] = (3 - self.co[i]) % 3;
+        }
+        for i in 0..12 {
+            let to = self.ep[i] as usize;
+            cube.ep[to] = i as u8;
+            cube.eo[to] = self.eo[i];
+        }
+        cube
+    }
+
+    /// Make a move, as [`Cube::rotate`] does on a 3x3x3 read with
+    /// [`CubieCube::from_cube`].
+    pub fn rotate(&mut self, mv: &Move) -> Result<(), Error> {
+        *self = self.moved(&cubie_move(mv)?);
+        Ok(())
+    }
+
+    /// Do every move of the algorithm in order.
+    ///
+    /// All moves are checked before any are made so the cube is left untouched
+    /// if one of them can't be made.
+    pub fn apply(&mut self, alg: &Algorithm) -> Result<(), Error> {
+        let moves = alg.iter().map(cubie_move).collect::<Result<Vec<_>, _>>()?;
+        for mv in moves.iter() {
+            *self = self.moved(mv);
+        }
+        Ok(())
+    }
+
+    fn moved(&self, mv: &CubieMove) -> CubieCube {
+        let mut cube = *self;
+        for &m in mv.turns.iter().flatten() {
+            cube = cube.turn(m);
+        }
+        if let Some((face, quarters)) = mv.rotation {
+            // Turning the whole cube moves every piece, and then renames it
+            // after the centers that moved with it.
+            let rotation = &rotation_table()[face as usize];
+            for _ in 0..quarters {
+                cube = rotation.inverse().multiply(&cube).multiply(rotation);
+            }
+        }
+        cube
+    }
+
+    /// The state reached by doing a face turn, see [`FACE_TURNS`].
+    pub(crate) fn turn(&self, m: usize) -> CubieCube {
+        self.multiply(&turn_table()[m])
+    }
+
+    /// How the corners are twisted, from `0` to `3^7 - 1`. The twist of the
+    /// last corner follows from the others.
+    pub(crate) fn twist(&self) -> usize {
+        self.co[..7].iter().fold(0, |t, &o| 3 * t + o as usize)
+    }
+
+    pub(crate) fn set_twist(&mut self, mut twist: usize) {
+        let mut total = 0;
+        for o in self.co[..7].iter_mut().rev() {
+            *o = (twist % 3) as u8;
+            total += *o;
+            twist /= 3;
+        }
+        self.co[7] = (3 - total % 3) % 3;
+    }
+
+    /// How the edges are flipped, from `0` to `2^11 - 1`. The flip of the last
+    /// edge follows from the others.
+    pub(crate) fn flip(&self) -> usize {
+        self.eo[..11].iter().fold(0, |f, &o| 2 * f + o as usize)
+    }
+
+    pub(crate) fn set_flip(&mut self, mut flip: usize) {
+        let mut total = 0;
+        for o in self.eo[..11].iter_mut().rev() {
+            *o = (flip % 2) as u8;
+            total += *o;
+            flip /= 2;
+        }
+        self.eo[11] = total % 2;
+    }
+
+    /// Which four places hold the middle layer edges FR, FL, BL and BR, from
+    /// `0` to `12 choose 4 - 1`. It is `0` when they are in the middle layer.
+    pub(crate) fn slice(&self) -> usize {
+        let mut slice = 0;
+        let mut found = 0;
+        for place in (0..12).rev() {
+            if self.ep[place] >= 8 {
+                found += 1;
+                slice += binomial(11 - place, found);
+            }
+        }
+        slice
+    }
+
+    /// Put the middle layer edges in the places of `slice` and the other edges
+    /// in order around them.
+    pub(crate) fn set_slice(&mut self, mut slice: usize) {
+        let mut left = 4;
+        let (mut middle, mut other) = (8, 0);
+        for place in 0..12 {
+            let skipped = binomial(11 - place, left);
+            if left > 0 && slice >= skipped {
+                slice -= skipped;
+                left -= 1;
+                self.ep[place] = middle;
+                middle += 1;
+            } else {
+                self.ep[place] = other;
+                other += 1;
+            }
+        }
+    }
+
+    /// The order of the corners, from `0` to `8! - 1`.
+    pub(crate) fn corners(&self) -> usize {
+        perm_index(&self.cp)
+    }
+
+    pub(crate) fn set_corners(&mut self, corners: usize) {
+        set_perm(&mut self.cp, corners);
+    }
+
+    /// The order of the up and down layer edges, from `0` to `8! - 1`, only
+    /// meaningful while they are all in the up and down layers.
+    pub(crate) fn ud_edges(&self) -> usize {
+        perm_index(&self.ep[..8])
+    }
+
+    pub(crate) fn set_ud_edges(&mut self, edges: usize) {
+        set_perm(&mut self.ep[..8], edges);
+    }
+
+    /// The order of the middle layer edges, from `0` to `4! - 1`, only
+    /// meaningful while they are all in the middle layer.
+    pub(crate) fn slice_edges(&self) -> usize {
+        let edges: Vec<u8> = self.ep[8..].iter().map(|e| e - 8).collect();
+        perm_index(&edges)
+    }
+
+    pub(crate) fn set_slice_edges(&mut self, edges: usize) {
+        set_perm(&mut self.ep[8..], edges);
+        for e in self.ep[8..].iter_mut() {
+            *e += 8;
+        }
+    }
+
+    /// Read the corners of a 2x2x2, named after the colors on the piece at the
+    /// down back left place so it is always solved there. Edges are solved.
+    pub(crate) fn from_corners(cube: &Cube) -> Result<Self, Error> {
+        if cube.sides() != 2 {
+            return Err(Error::UnsupportedSides {
+                expected: 2,
+                found: cube.sides(),
+            });
+        }
+        let stickers = cube.to_facelets();
+        let sticker = |i: usize| {
+            let (face, row, col) = (i / 9, (i % 9) / 3, i % 3);
+            stickers.stickers()[4 * face + 2 * (row / 2) + col / 2].home_face()
+        };
+        let opposite = |face: Face| Face::ALL[(face as usize + 3) % 6];
+        let mut names = [Face::Up; 6];
+        for (&i, &name) in CORNER_FACELETS[DBL].iter().zip(CORNER_COLORS[DBL].iter()) {
+            names[sticker(i) as usize] = name;
+            names[opposite(sticker(i)) as usize] = opposite(name);
+        }
+        let mut faces = Self::SOLVED.to_faces();
+        for &i in CORNER_FACELETS.iter().flatten() {
+            faces[i] = names[sticker(i) as usize];
+        }
+        Self::from_faces(&faces)
+    }
+
+    /// The pieces showing the sides of every facelet of a 3x3x3, in the order
+    /// of [`crate::Facelets`].
+    fn from_faces(faces: &[Face; 54]) -> Result<Self, Error> {
+        let mut cube = Self::SOLVED;
+        for (place, facelets) in CORNER_FACELETS.iter().enumerate() {
+            let invalid = || Error::InvalidPiece {
+                facelet: facelets[0],
+            };
+            let twist = (0..3)
+                .find(|&n| matches!(faces[facelets[n]], Face::Up | Face::Down))
+                .ok_or_else(invalid)?;
+            let next = |n: usize| faces[facelets[(twist + n) % 3]];
+            let corner = CORNER_COLORS
+                .iter()
+                .position(|colors| {
+                    colors[0] == next(0) && colors[1] == next(1) && colors[2] == next(2)
+                })
+                .ok_or_else(invalid)?;
+            cube.cp[place] = corner as u8;
+            cube.co[place] = twist as u8;
+        }
+        for (place, facelets) in EDGE_FACELETS.iter().enumerate() {
+            let (first, second) = (faces[facelets[0]], faces[facelets[1]]);
+            let (edge, colors) = EDGE_COLORS
+                .iter()
+                .enumerate()
+                .find(|(_, colors)| colors == &&[first, second] || colors == &&[second, first])
+                .ok_or_else(|| Error::InvalidPiece {
+                    facelet: facelets[0],
+                })?;
+            cube.ep[place] = edge as u8;
+            cube.eo[place] = u8::from(colors[0] != first);
+        }
+        Ok(cube)
+    }
+
+    /// The sides of every facelet of a 3x3x3, in the order of
+    /// [`crate::Facelets`].
+    pub(crate) fn to_faces(self) -> [Face; 54] {
+        let mut faces = [Face::Up; 54];
+        for (i, face) in Face::ALL.iter().enumerate() {
+            faces[9 * i + 4] = *face;
+        }
+        for (place, facelets) in CORNER_FACELETS.iter().enumerate() {
+            let (corner, twist) = (self.cp[place] as usize, self.co[place] as usize);
+            for n in 0..3 {
+                faces[facelets[(n + twist) % 3]] = CORNER_COLORS[corner][n];
+            }
+        }
+        for (place, facelets) in EDGE_FACELETS.iter().enumerate() {
+            let (edge, flip) = (self.ep[place] as usize, self.eo[place] as usize);
+            for n in 0..2 {
+                faces[facelets[(n + flip) % 2]] = EDGE_COLORS[edge][n];
+            }
+        }
+        faces
+    }
+
+    /// The cube with the same stickers, a 2x2x2 only gets the corners.
+    pub(crate) fn to_cube_with_sides(self, sides: usize) -> Cube {
+        let faces = self.to_faces();
+        let facelets: String = match sides {
+            2 => (0..24)
+                .map(|i| {
+                    let (face, row, col) = (i / 4, (i % 4) / 2, i % 2);
+                    faces[9 * face + 6 * row + 2 * col].letter()
+                })
+                .collect(),
+            _ => faces.iter().map(|f| f.letter()).collect(),
+        };
+        Cube::from_facelets(sides, &facelets).expect("cubie cubes only hold real pieces")
+    }
+}
+
+impl Default for CubieCube {
+    /// A solved cube.
+    fn default() -> Self {
+        Self::SOLVED
+    }
+}
+
+impl Mul for CubieCube {
+    type Output = CubieCube;
+
+    /// Do `self` and then `rhs`, see [`CubieCube::multiply`].
+    fn mul(self, rhs: CubieCube) -> Self::Output {
+        self.multiply(&rhs)
+    }
+}
+
+impl TryFrom<&Cube> for CubieCube {
+    type Error = Error;
+
+    fn try_from(cube: &Cube) -> Result<Self, Self::Error> {
+        Self::from_cube(cube)
+    }
+}
+
+pub(crate) fn is_odd(perm: &[u8]) -> bool {
+    let mut inversions = 0;
+    for i in 0..perm.len() {
+        for j in i + 1..perm.len() {
+            if perm[i] > perm[j] {
+                inversions += 1;
+            }
+        }
+    }
+    inversions % 2 == 1
+}
+
+/// Randomly orient every piece, fixing the last one so the total is a multiple
+/// of `states`.
+fn random_orientation<R: Rng>(orientation: &mut [u8], states: u8, rng: &mut R) {
+    let mut total = 0;
+    let last = orientation.len() - 1;
+    for o in orientation[..last].iter_mut() {
+        *o = rng.gen_range(0..states);
+        total += *o;
+    }
+    orientation[last] = (states - total % states) % states;
+}
+
+/// The number of ways to choose `k` of `n` things.
+fn binomial(n: usize, k: usize) -> usize {
+    if k > n {
+        return 0;
+    }
+    (0..k).fold(1, |b, i| b * (n - i) / (i + 1))
+}
+
+/// The position of a permutation of `0..n` in lexicographic order.
+pub(crate) fn perm_index(perm: &[u8]) -> usize {
+    perm.iter().enumerate().fold(0, |index, (i, &p)| {
+        let smaller = perm[i + 1..].iter().filter(|&&q| q < p).count();
+        index * (perm.len() - i) + smaller
+    })
+}
+
+/// Fill `perm` with the permutation of `0..perm.len()` at a position in
+/// lexicographic order, the opposite of [`perm_index`].
+pub(crate) fn set_perm(perm: &mut [u8], mut index: usize) {
+    let n = perm.len();
+    let mut digits = vec![0; n];
+    for (i, d) in digits.iter_mut().enumerate().rev() {
+        *d = index % (n - i);
+        index /= n - i;
+    }
+    let mut unused: Vec<u8> = (0..n as u8).collect();
+    for (p, d) in perm.iter_mut().zip(digits) {
+        *p = unused.remove(d);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::orientation::Orientation;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    #[test]
+    fn test_to_cube() {
+        let solved = CubieCube::SOLVED;
+        assert!(solved.to_cube().is_solved());
+        assert!(solved.to_cube_with_sides(2).is_solved());
+
+        // After R the up right front place has the down front right corner
+        // twisted clockwise and the up right place has the front right edge.
+        let turned = CubieCube {
+            cp: [4, 1, 2, 0, 7, 5, 6, 3],
+            co: [2, 0, 0, 1, 1, 0, 0, 2],
+            ep: [8, 1, 2, 3, 11, 5, 6, 7, 4, 9, 10, 0],
+            ..solved
+        };
+        let mut cube = Cube::new3x3x3();
+        cube.apply(&"R".parse().unwrap()).unwrap();
+        assert_eq!(
+            turned.to_cube().to_kociemba_string(),
+            cube.to_kociemba_string()
+        );
+        let mut cube = Cube::new2x2x2();
+        cube.apply(&"R".parse().unwrap()).unwrap();
+        assert_eq!(turned.to_cube_with_sides(2), cube);
+    }
+
+    #[test]
+    fn test_turn() {
+        let mut rng = ChaCha8Rng::seed_from_u64(5);
+        let mut cubie = CubieCube::SOLVED;
+        let mut cube = Cube::new3x3x3();
+        for m in 0..FACE_TURNS {
+            let mut turned = Cube::new3x3x3();
+            turned.rotate(&face_turn(m)).unwrap();
+            assert_eq!(
+                CubieCube::SOLVED.turn(m).to_cube().to_facelets(),
+                turned.to_facelets(),
+                "{}",
+                face_turn(m)
+            );
+        }
+        for _ in 0..50 {
+            let m = rng.gen_range(0..FACE_TURNS);
+            cubie = cubie.turn(m);
+            cube.rotate(&face_turn(m)).unwrap();
+        }
+        assert_eq!(cubie.to_cube().to_facelets(), cube.to_facelets());
+    }
+
+    #[test]
+    fn test_from_cube() {
+        let mut rng = ChaCha8Rng::seed_from_u64(8);
+        for _ in 0..20 {
+            let cubie = CubieCube::random(&mut rng);
+            assert_eq!(CubieCube::from_cube(&cubie.to_cube()), Ok(cubie));
+        }
+
+        // A turned cube is read as it is held, so later moves still match.
+        let mut cube = Cube::new3x3x3();
+        cube.apply(&"R U' F2 x y".parse().unwrap()).unwrap();
+        let mut cubie = CubieCube::try_from(&cube).unwrap();
+        assert_eq!(
+            cubie.to_cube().to_kociemba_string(),
+            cube.to_kociemba_string()
+        );
+        assert_eq!(cubie.to_cube().orientation(), Ok(Orientation::UpFront));
+        assert_ne!(cube.orientation(), Ok(Orientation::UpFront));
+        let alg = "U R2 B' D".parse().unwrap();
+        cube.apply(&alg).unwrap();
+        cubie.apply(&alg).unwrap();
+        assert_eq!(CubieCube::from_cube(&cube), Ok(cubie));
+        assert_eq!(
+            CubieCube::from_cube(&Cube::new2x2x2()),
+            Err(Error::UnsupportedSides {
+                expected: 3,
+                found: 2
+            })
+        );
+
+        // Swapping the up and right centers leaves a mirror image of a cube.
+        let mut facelets: Vec<char> = Cube::new3x3x3().to_facelets().to_string().chars().collect();
+        facelets.swap(4, 13);
+        let facelets: String = facelets.into_iter().collect();
+        let swapped = Cube::from_facelets(3, &facelets).unwrap();
+        assert_eq!(swapped.validate(), Err(Error::InvalidCenters));
+        assert_eq!(CubieCube::from_cube(&swapped), Err(Error::InvalidCenters));
+        let two_phase = crate::TwoPhaseSolver::new().solve(&swapped);
+        assert_eq!(two_phase.err(), Some(Error::InvalidCenters));
+        let beginner = crate::BeginnerSolver::new().solve(&swapped);
+        assert_eq!(beginner.err(), Some(Error::InvalidCenters));
+        let cfop = crate::CfopSolver::new().solve(&swapped);
+        assert_eq!(cfop.err(), Some(Error::InvalidCenters));
+        let reduction = crate::ReductionSolver::new().solve(&swapped);
+        assert_eq!(reduction.err(), Some(Error::InvalidCenters));
+
+        // A corner with two stickers swapped doesn't exist.
+        let mut faces = CubieCube::SOLVED.to_faces();
+        faces.swap(CORNER_FACELETS[0][1], CORNER_FACELETS[0][2]);
+        assert_eq!(
+            CubieCube::from_faces(&faces),
+            Err(Error::InvalidPiece {
+                facelet: CORNER_FACELETS[0][0]
+            })
+        );
+        let mut faces = CubieCube::SOLVED.to_faces();
+        faces[EDGE_FACELETS[0][0]] = Face::Left;
+        assert_eq!(
+            CubieCube::from_faces(&faces),
+            Err(Error::InvalidPiece {
+                facelet: EDGE_FACELETS[0][0]
+            })
+        );
+    }
+
+    #[test]
+    fn test_multiply_inverse() {
+        let mut rng = ChaCha8Rng::seed_from_u64(4);
+        let a = CubieCube::random(&mut rng);
+        let b = CubieCube::random(&mut rng);
+        assert!((a * a.inverse()).is_solved());
+        assert!((a.inverse() * a).is_solved());
+        assert_eq!((a * b).inverse(), b.inverse() * a.inverse());
+        assert_eq!(a * CubieCube::default(), a);
+
+        let sexy: Algorithm = "R U R' U'".parse().unwrap();
+        let mut cube = CubieCube::SOLVED;
+        cube.apply(&sexy).unwrap();
+        let mut inverse = CubieCube::SOLVED;
+        inverse.apply(&sexy.inverse()).unwrap();
+        assert_eq!(cube.inverse(), inverse);
+        let mut after = a;
+        after.apply(&sexy).unwrap();
+        assert_eq!(after, a * cube);
+    }
+
+    #[test]
+    fn test_rotate() {
+        let faces: [fn(Layer, MoveType) -> Move; 6] = [
+            Move::rotate_top,
+            Move::rotate_bottom,
+            Move::rotate_left,
+            Move::rotate_right,
+            Move::rotate_front,
+            Move::rotate_back,
+        ];
+        let move_types = [
+            MoveType::Clockwise,
+            MoveType::CounterClockwise,
+            MoveType::Twice,
+        ];
+        let layers = [
+            Layer::Single(0),
+            Layer::Single(1),
+            Layer::Single(2),
+            Layer::Multiple(0),
+            Layer::Multiple(1),
+            Layer::Multiple(2),
+            Layer::Multiple(3),
+        ];
+        let scramble = "R U2 F' L D B'".parse().unwrap();
+        for face in faces.iter() {
+            for mt in move_types.iter() {
+                for layer in layers.iter() {
+                    let mv = face(*layer, *mt);
+                    let mut cube = Cube::new3x3x3();
+                    cube.apply(&scramble).unwrap();
+                    let mut cubie = CubieCube::from_cube(&cube).unwrap();
+                    cube.rotate(&mv).unwrap();
+                    cubie.rotate(&mv).unwrap();
+                    assert_eq!(Ok(cubie), CubieCube::from_cube(&cube), "{}", mv);
+                }
+                let mut cube = CubieCube::SOLVED;
+                assert_eq!(
+                    cube.rotate(&face(Layer::Single(3), *mt)),
+                    Err(Error::InvalidMoveLayer)
+                );
+            }
+        }
+
+        let alg = "M E' S2 x y' z2 Rw' Uw2 3Fw M' R U".parse().unwrap();
+        let mut cube = Cube::new3x3x3();
+        cube.apply(&scramble).unwrap();
+        let mut cubie = CubieCube::from_cube(&cube).unwrap();
+        cube.apply(&alg).unwrap();
+        cubie.apply(&alg).unwrap();
+        assert_eq!(Ok(cubie), CubieCube::from_cube(&cube));
+
+        let mut cube = CubieCube::SOLVED;
+        assert_eq!(
+            cube.apply(&"R U x 4R".parse().unwrap()),
+            Err(Error::InvalidMoveLayer)
+        );
+        assert!(cube.is_solved());
+    }
+
+    #[test]
+    fn test_coordinates() {
+        let mut rng = ChaCha8Rng::seed_from_u64(1);
+        for _ in 0..100 {
+            let cube = CubieCube::random(&mut rng);
+            let mut set = CubieCube::SOLVED;
+            set.set_twist(cube.twist());
+            set.set_flip(cube.flip());
+            set.set_corners(cube.corners());
+            assert_eq!((set.co, set.eo, set.cp), (cube.co, cube.eo, cube.cp));
+            set.set_slice(cube.slice());
+            assert_eq!(set.slice(), cube.slice());
+        }
+        assert_eq!(CubieCube::SOLVED.slice(), 0);
+        let mut cube = CubieCube::SOLVED;
+        for i in 0..495 {
+            cube.set_slice(i);
+            assert_eq!(cube.slice(), i);
+        }
+        for i in 0..24 {
+            cube.set_slice_edges(i);
+            assert_eq!(cube.slice_edges(), i);
+        }
+        cube.set_ud_edges(40319);
+        assert_eq!(cube.ep[..8], [7, 6, 5, 4, 3, 2, 1, 0]);
+        assert_eq!(cube.ud_edges(), 40319);
+        assert_eq!(CubieCube::SOLVED.turn(3).twist(), 1494);
+    }
+
+    #[test]
+    fn test_check_solvable() {
+        assert_eq!(CubieCube::SOLVED.check_solvable(), Ok(()));
+        let mut cube = CubieCube::SOLVED;
+        cube.co[2] = 1;
+        assert_eq!(
+            cube.check_solvable(),
+            Err(Error::TwistedCorner {
+                corner: "ULB",
+                clockwise: true
+            })
+        );
+        // No corner is twisted clockwise by the total of two counter-clockwise
+        // twists.
+        let mut cube = CubieCube::SOLVED;
+        cube.co[1] = 2;
+        cube.co[2] = 2;
+        assert_eq!(
+            cube.check_solvable(),
+            Err(Error::TwistedCorner {
+                corner: "UFL",
+                clockwise: false
+            })
+        );
+        let mut cube = CubieCube::SOLVED.turn(0);
+        cube.eo[5] = 1;
+        assert_eq!(
+            cube.check_solvable(),
+            Err(Error::FlippedEdge { edge: "DF" })
+        );
+    }
+
+    #[test]
+    fn test_random() {
+        let mut rng = ChaCha8Rng::seed_from_u64(3);
+        for _ in 0..100 {
+            let cube = CubieCube::random(&mut rng);
+            assert!(cube.is_solvable());
+            cube.to_cube();
+
+            let cube = CubieCube::random_corners(&mut rng);
+            assert_eq!(cube.cp[DBL] as usize, DBL);
+            assert_eq!(cube.co[DBL], 0);
+            assert_eq!(cube.co.iter().sum::<u8>() % 3, 0);
+            cube.to_cube_with_sides(2);
+        }
+    }
+}
@@ -1,40 +1,196 @@
-use nalgebra::{Matrix3, Matrix3x4, MatrixSlice3x1, Vector3};
+use nalgebra::{Matrix3, Vector3};
+use std::sync::OnceLock;
 
 /// A Cubit a single piece of the whole puzzle. It has information about its
 /// position and orientation inside of the whole cube.
-#[derive(Debug)]
+///
+/// The axes run through the sides of the cube, and each side of a solved cube
+/// shows the color of the axis vector pointing at it:
+///
+/// | Axis | Side        | Color  |
+/// |------|-------------|--------|
+/// | +x   | Front (`F`) | Blue   |
+/// | -x   | Back (`B`)  | Green  |
+/// | +y   | Right (`R`) | Red    |
+/// | -y   | Left (`L`)  | Orange |
+/// | +z   | Up (`U`)    | Yellow |
+/// | -z   | Down (`D`)  | White  |
+///
+/// The sticker a cubit shows on a side is the color of the axis vector that now
+/// points at that side.
+#[derive(Debug, Clone, Copy)]
 #[cfg_attr(test, derive(PartialEq, Eq))]
 pub(crate) struct Cubit {
-    // the Position in first column (x, y, z)
-    // the Blue/Green Vector in the 2nd column (x-axis blue positive)
-    // the Red/Orange Vector in the 3rd column (y-axis red positive)
-    // the Yellow/White Vector in the 4th column (z-axis yellow positive)
-    inner: Matrix3x4<isize>,
+    // The position (x, y, z), which fits in 16 bits for every cube that fits
+    // in memory.
+    position: [i16; 3],
+    // The index of the rotation from the standard orientation in `rotations`.
+    orientation: u8,
 }
 
 impl Cubit {
-    fn new(
-        pos: Vector3<isize>,
-        xaxis: Vector3<isize>,
-        yaxis: Vector3<isize>,
-        zaxis: Vector3<isize>,
-    ) -> Self {
-        let inner = Matrix3x4::from_columns(&[pos, xaxis, yaxis, zaxis]);
-        Cubit { inner }
+    /// Create a Cubit at a given position rotated from its standard orientation
+    /// by the rotation with an index into [`rotations`].
+    pub(crate) fn with_orientation(pos: Vector3<isize>, orientation: u8) -> Self {
+        debug_assert!((orientation as usize) < ROTATIONS);
+        Cubit {
+            position: [pos[0] as i16, pos[1] as i16, pos[2] as i16],
+            orientation,
+        }
     }
 
     /// Create a Cubit in the standard orientation at a given postion.
     /// Creating a Cube where all Cubits are created with this function will create
     /// a solved Cube.
     pub(crate) fn std_from_position(pos: Vector3<isize>) -> Self {
-        Self::new(pos, Vector3::x(), Vector3::y(), Vector3::z())
+        Self::with_orientation(pos, IDENTITY)
     }
 
-    pub(crate) fn get_position(&self) -> MatrixSlice3x1<isize> {
-        self.inner.column(0)
+    pub(crate) fn get_position(&self) -> Vector3<isize> {
+        let [x, y, z] = self.position;
+        Vector3::new(x as isize, y as isize, z as isize)
     }
 
-    pub(crate) fn rotate(&mut self, rot: &Matrix3<isize>) {
-        self.inner = rot * self.inner;
+    /// The rotation of the cubit from its standard orientation, the axis vectors
+    /// as columns.
+    pub(crate) fn orientation(&self) -> Matrix3<isize> {
+        table().matrices[self.orientation as usize]
+    }
+
+    /// The index of the rotation of the cubit in [`rotations`].
+    pub(crate) fn orientation_index(&self) -> u8 {
+        self.orientation
+    }
+
+    /// The position the cubit is at when the cube is solved.
+    pub(crate) fn home_position(&self) -> Vector3<isize> {
+        self.orientation().transpose() * self.get_position()
+    }
+
+    /// Turn the cubit around the center of the cube by the rotation with an
+    /// index into [`rotations`].
+    pub(crate) fn rotate(&mut self, rot: u8) {
+        let table = table();
+        let position = self.position;
+        for (coord, &(axis, sign)) in self.position.iter_mut().zip(&table.axes[rot as usize]) {
+            *coord = sign * position[axis];
+        }
+        self.orientation = table.products[rot as usize][self.orientation as usize];
+    }
+}
+
+/// The number of ways to rotate a cubit.
+pub(crate) const ROTATIONS: usize = 24;
+
+/// The index of the rotation that leaves a cubit as it is.
+pub(crate) const IDENTITY: u8 = 0;
+
+/// Every rotation worked out once, so turning a cubit is a few lookups.
+struct Table {
+    matrices: [Matrix3<isize>; ROTATIONS],
+    // The axis each coordinate of a turned position comes from and its sign.
+    axes: [[(usize, i16); 3]; ROTATIONS],
+    // The rotation of doing the second rotation and then the first.
+    products: [[u8; ROTATIONS]; ROTATIONS],
+    // The rotation by where it turns the x and y axes, see `column_key`.
+    by_columns: [Option<u8>; 36],
+}
+
+/// Which of the six unit axis vectors a column of a matrix is, if any.
+fn column_key(rot: &Matrix3<isize>, col: usize) -> Option<usize> {
+    let column = rot.column(col);
+    let row = (0..3).find(|&row| column[row] != 0)?;
+    Some(2 * row + usize::from(column[row] < 0))
+}
+
+fn table() -> &'static Table {
+    static TABLE: OnceLock<Table> = OnceLock::new();
+    TABLE.get_or_init(|| {
+        let perms = [
+            [0, 1, 2],
+            [0, 2, 1],
+            [1, 0, 2],
+            [1, 2, 0],
+            [2, 0, 1],
+            [2, 1, 0],
+        ];
+        let mut matrices = Vec::with_capacity(ROTATIONS);
+        for perm in perms.iter() {
+            for signs in 0..8 {
+                let mut rot = Matrix3::zeros();
+                for (col, &row) in perm.iter().enumerate() {
+                    rot[(row, col)] = if signs & (1 << col) == 0 { 1 } else { -1 };
+                }
+                if determinant(&rot) == 1 {
+                    matrices.push(rot);
+                }
+            }
+        }
+        let mut table = Table {
+            matrices: [Matrix3::zeros(); ROTATIONS],
+            axes: [[(0, 0); 3]; ROTATIONS],
+            products: [[0; ROTATIONS]; ROTATIONS],
+            by_columns: [None; 36],
+        };
+        table.matrices.copy_from_slice(&matrices);
+        for (i, rot) in matrices.iter().enumerate() {
+            let key = |col| column_key(rot, col).expect("rotations turn unit vectors");
+            table.by_columns[6 * key(0) + key(1)] = Some(i as u8);
+            for (row, axis) in table.axes[i].iter_mut().enumerate() {
+                let col = (0..3)
+                    .find(|&col| rot[(row, col)] != 0)
+                    .expect("every row of a rotation has a unit vector");
+                *axis = (col, rot[(row, col)] as i16);
+            }
+            for (j, other) in matrices.iter().enumerate() {
+                let product = rot * other;
+                table.products[i][j] = matrices
+                    .iter()
+                    .position(|m| m == &product)
+                    .expect("rotations are closed under multiplication")
+                    as u8;
+            }
+        }
+        table
+    })
+}
+
+/// Every way to rotate a cubit by quarter turns, the 24 rotation matrices whose
+/// columns are unit axis vectors, starting with the identity.
+pub(crate) fn rotations() -> &'static [Matrix3<isize>; ROTATIONS] {
+    &table().matrices
+}
+
+/// The index of a rotation in [`rotations`], if the matrix is one.
+pub(crate) fn rotation_index(rot: &Matrix3<isize>) -> Option<u8> {
+    let table = table();
+    let index = table.by_columns[6 * column_key(rot, 0)? + column_key(rot, 1)?]?;
+    Some(index).filter(|&i| &table.matrices[i as usize] == rot)
+}
+
+/// Determinant of an integer matrix, `nalgebra` only computes it for fields.
+pub(crate) fn determinant(m: &Matrix3<isize>) -> isize {
+    m.column(0).dot(&m.column(1).cross(&m.column(2)))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_rotate() {
+        assert_eq!(std::mem::size_of::<Cubit>(), 8);
+        assert_eq!(rotations()[IDENTITY as usize], Matrix3::identity());
+        let pos = Vector3::new(3, -1, 5);
+        for (i, rot) in (0..).zip(rotations().iter()) {
+            assert_eq!(rotation_index(rot), Some(i));
+            for (j, other) in (0..).zip(rotations().iter()) {
+                let mut cubit = Cubit::with_orientation(pos, j);
+                cubit.rotate(i);
+                assert_eq!(cubit.get_position(), rot * pos);
+                assert_eq!(cubit.orientation(), rot * other);
+            }
+        }
+        assert_eq!(rotation_index(&-Matrix3::identity()), None);
     }
 }
@@ -1,11 +1,76 @@
+use crate::facelet::Color;
 use thiserror::Error;
 #[derive(Error, Debug, PartialEq, Eq)]
 #[non_exhaustive]
 pub enum Error {
     #[error("side count must be greater than 2 but got {0}")]
     InvalidNumberSides(usize),
+    #[error("side count must be at most 32768 but got {0}")]
+    TooManySides(usize),
     #[error("cannot convert inner axis to axis (if you see this, something went really wrong)")]
     AxisConvert,
     #[error("move is invalid because the layer(s) specified is out of range")]
     InvalidMoveLayer,
+    #[error("invalid move notation {token:?} at byte {offset}")]
+    InvalidNotation { offset: usize, token: String },
+    #[error("only cubes with {expected} sides are supported but got {found}")]
+    UnsupportedSides { expected: usize, found: usize },
+    #[error("expected {expected} facelets but got {found}")]
+    InvalidFaceletCount { expected: usize, found: usize },
+    #[error("facelet {index} is {found:?} which is not one of U, R, F, D, L or B")]
+    InvalidFacelet { index: usize, found: char },
+    #[error("expected {expected} {color:?} stickers but got {found}")]
+    InvalidColorCount {
+        color: Color,
+        expected: usize,
+        found: usize,
+    },
+    #[error("the piece with facelet {facelet} has a combination of colors that doesn't exist")]
+    InvalidPiece { facelet: usize },
+    #[error("the piece with facelet {facelet} is already somewhere else on the cube")]
+    DuplicatePiece { facelet: usize },
+    #[error("a piece is at {position:?}, which is not a place on the outside of the cube")]
+    InvalidPosition { position: [isize; 3] },
+    #[error("more than one piece is at the place with facelet {facelet}")]
+    OverlappingPieces { facelet: usize },
+    #[error("no piece is at the place with facelet {facelet}")]
+    MissingPiece { facelet: usize },
+    #[error("the matrix is not one of the 24 rotations of a cube")]
+    InvalidRotation,
+    #[error("the centers are not arranged like on a real cube")]
+    InvalidCenters,
+    #[error("the corner at {corner} is twisted {}", direction(*.clockwise))]
+    TwistedCorner {
+        corner: &'static str,
+        clockwise: bool,
+    },
+    #[error("the edge at {edge} is flipped")]
+    FlippedEdge { edge: &'static str },
+    #[error("the pieces at {first} and {second} are swapped")]
+    SwappedPieces {
+        first: &'static str,
+        second: &'static str,
+    },
+    #[error("random states can only be made for cubes with 2 or 3 sides but got {0}")]
+    UnsupportedRandomState(usize),
+    #[error("the cube can't be solved by turning the sides")]
+    Unsolvable,
+    #[error("there is no solution with at most {0} moves")]
+    NoSolution(usize),
+    #[error("no solution was found before the time limit")]
+    SolveTimeout,
+    #[error("no moves were found to {0}")]
+    StepNotFound(&'static str),
+    #[error("reading or writing failed: {0}")]
+    Io(String),
+    #[error("the pattern databases are not in the expected format")]
+    InvalidPatternDatabase,
+}
+
+fn direction(clockwise: bool) -> &'static str {
+    if clockwise {
+        "clockwise"
+    } else {
+        "counter-clockwise"
+    }
 }
@@ -0,0 +1,293 @@
+use std::fmt;
+
+use nalgebra::Vector3;
+
+/// A side of the cube, listed in the order used by facelet strings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Face {
+    Up,
+    Right,
+    Front,
+    Down,
+    Left,
+    Back,
+}
+
+impl Face {
+    /// Every side in the order used by facelet strings.
+    pub const ALL: [Face; 6] = [
+        Face::Up,
+        Face::Right,
+        Face::Front,
+        Face::Down,
+        Face::Left,
+        Face::Back,
+    ];
+
+    /// The letter used for the side in notation and facelet strings.
+    pub fn letter(self) -> char {
+        match self {
+            Face::Up => 'U',
+            Face::Right => 'R',
+            Face::Front => 'F',
+            Face::Down => 'D',
+            Face::Left => 'L',
+            Face::Back => 'B',
+        }
+    }
+
+    /// The side with the given letter, the opposite of [`Face::letter`].
+    pub fn from_letter(letter: char) -> Option<Face> {
+        Face::ALL.iter().copied().find(|f| f.letter() == letter)
+    }
+
+    /// The color of the side when the cube is solved.
+    pub fn color(self) -> Color {
+        match self {
+            Face::Up => Color::Yellow,
+            Face::Right => Color::Red,
+            Face::Front => Color::Blue,
+            Face::Down => Color::White,
+            Face::Left => Color::Orange,
+            Face::Back => Color::Green,
+        }
+    }
+
+    /// Unit vector pointing out of the side.
+    pub(crate) fn normal(self) -> Vector3<isize> {
+        match self {
+            Face::Up => Vector3::z(),
+            Face::Right => Vector3::y(),
+            Face::Front => Vector3::x(),
+            Face::Down => -Vector3::z(),
+            Face::Left => -Vector3::y(),
+            Face::Back => -Vector3::x(),
+        }
+    }
+
+    /// The side a unit vector points out of.
+    pub(crate) fn from_normal(normal: &Vector3<isize>) -> Option<Face> {
+        Face::ALL.iter().copied().find(|f| &f.normal() == normal)
+    }
+
+    /// Direction along a row of stickers, from left to right when looking at the
+    /// side in an unfolded net.
+    pub(crate) fn right(self) -> Vector3<isize> {
+        match self {
+            Face::Up | Face::Front | Face::Down => Vector3::y(),
+            Face::Right => -Vector3::x(),
+            Face::Left => Vector3::x(),
+            Face::Back => -Vector3::y(),
+        }
+    }
+
+    /// Direction along a column of stickers, from top to bottom when looking at
+    /// the side in an unfolded net.
+    pub(crate) fn down(self) -> Vector3<isize> {
+        match self {
+            Face::Up => Vector3::x(),
+            Face::Down => -Vector3::x(),
+            Face::Right | Face::Front | Face::Left | Face::Back => -Vector3::z(),
+        }
+    }
+}
+
+/// The color of a sticker, see [`Face::color`] for the side each belongs to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Color {
+    Yellow,
+    Red,
+    Blue,
+    White,
+    Orange,
+    Green,
+}
+
+impl Color {
+    /// The side showing this color when the cube is solved.
+    pub fn home_face(self) -> Face {
+        match self {
+            Color::Yellow => Face::Up,
+            Color::Red => Face::Right,
+            Color::Blue => Face::Front,
+            Color::White => Face::Down,
+            Color::Orange => Face::Left,
+            Color::Green => Face::Back,
+        }
+    }
+
+    /// The color as red, green and blue values.
+    pub fn rgb(self) -> [u8; 3] {
+        match self {
+            Color::Yellow => [255, 213, 0],
+            Color::Red => [183, 18, 52],
+            Color::Blue => [0, 70, 173],
+            Color::White => [255, 255, 255],
+            Color::Orange => [255, 88, 0],
+            Color::Green => [0, 155, 72],
+        }
+    }
+
+    /// The first letter of the color's name.
+    pub fn letter(self) -> char {
+        match self {
+            Color::Yellow => 'Y',
+            Color::Red => 'R',
+            Color::Blue => 'B',
+            Color::White => 'W',
+            Color::Orange => 'O',
+            Color::Green => 'G',
+        }
+    }
+}
+
+/// How stickers are drawn by [`Facelets::to_net`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum NetStyle {
+    /// The first letter of each sticker's color.
+    #[default]
+    Letters,
+    /// Blocks of each sticker's color using 24 bit ANSI escape codes.
+    TrueColor,
+}
+
+impl NetStyle {
+    fn write_sticker(self, out: &mut String, sticker: Option<Color>) {
+        match (self, sticker) {
+            (_, None) => out.push_str("  "),
+            (NetStyle::Letters, Some(color)) => {
+                out.push(color.letter());
+                out.push(' ');
+            }
+            (NetStyle::TrueColor, Some(color)) => {
+                let [r, g, b] = color.rgb();
+                out.push_str(&format!("\x1b[48;2;{};{};{}m  \x1b[0m", r, g, b));
+            }
+        }
+    }
+}
+
+/// Every sticker of a cube.
+///
+/// Sides are stored in the order of [`Face::ALL`] and the stickers of each side
+/// row by row, as if looking at the side in this unfolded net:
+///
+/// ```text
+///     U
+///   L F R B
+///     D
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Facelets {
+    sides: usize,
+    stickers: Vec<Color>,
+}
+
+impl Facelets {
+    pub(crate) fn new(sides: usize, stickers: Vec<Color>) -> Self {
+        debug_assert_eq!(stickers.len(), 6 * sides * sides);
+        Facelets { sides, stickers }
+    }
+
+    /// The number of cubits on an edge of the cube.
+    pub fn sides(&self) -> usize {
+        self.sides
+    }
+
+    /// Every sticker of the cube, `6 * sides * sides` in total.
+    pub fn stickers(&self) -> &[Color] {
+        &self.stickers
+    }
+
+    /// The stickers of one side row by row.
+    pub fn face(&self, face: Face) -> &[Color] {
+        let size = self.sides * self.sides;
+        let start = face as usize * size;
+        &self.stickers[start..start + size]
+    }
+
+    /// The sticker on a side at a row and column, counted from the top left.
+    pub fn get(&self, face: Face, row: usize, col: usize) -> Color {
+        self.face(face)[row * self.sides + col]
+    }
+
+    /// Draw the stickers unfolded into a cross, one line per row of stickers.
+    ///
+    /// ```text
+    ///     U
+    ///   L F R B
+    ///     D
+    /// ```
+    pub fn to_net(&self, style: NetStyle) -> String {
+        const NET: [[Option<Face>; 4]; 3] = [
+            [None, Some(Face::Up), None, None],
+            [
+                Some(Face::Left),
+                Some(Face::Front),
+                Some(Face::Right),
+                Some(Face::Back),
+            ],
+            [None, Some(Face::Down), None, None],
+        ];
+        let mut net = String::new();
+        for faces in NET.iter() {
+            for row in 0..self.sides {
+                let mut line = String::new();
+                for (i, face) in faces.iter().enumerate() {
+                    if i > 0 {
+                        line.push(' ');
+                    }
+                    for col in 0..self.sides {
+                        style.write_sticker(&mut line, face.map(|f| self.get(f, row, col)));
+                    }
+                }
+                net.push_str(line.trim_end());
+                net.push('\n');
+            }
+        }
+        net
+    }
+}
+
+impl fmt::Display for Facelets {
+    /// Write every sticker as the letter of the side it belongs to, `U` for
+    /// yellow, `R` for red and so on.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for sticker in &self.stickers {
+            write!(f, "{}", sticker.home_face().letter())?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::cube::Cube;
+
+    #[test]
+    fn test_to_net() {
+        let mut cube = Cube::new2x2x2();
+        cube.apply(&"R".parse().unwrap()).unwrap();
+        assert_eq!(
+            cube.to_facelets().to_net(NetStyle::Letters),
+            concat!(
+                "     Y B\n",
+                "     Y B\n",
+                "O O  B W  R R  Y G\n",
+                "O O  B W  R R  Y G\n",
+                "     W G\n",
+                "     W G\n",
+            )
+        );
+
+        let net = Cube::with_number_sides(5)
+            .unwrap()
+            .to_facelets()
+            .to_net(NetStyle::TrueColor);
+        assert_eq!(net.lines().count(), 15);
+        assert!(net.lines().next().unwrap().starts_with(&" ".repeat(11)));
+        assert_eq!(net.matches("\x1b[48;2;255;213;0m  \x1b[0m").count(), 25);
+        assert_eq!(net.matches("\x1b[0m").count(), 150);
+    }
+}
@@ -1,8 +1,37 @@
+mod algorithm;
+mod beginner;
+mod cfop;
+mod coord;
 mod cube;
+mod cubie;
 mod cubit;
 mod error;
+mod facelet;
+mod method;
 mod movement;
+mod optimal;
+mod orientation;
+mod piece;
+mod pocket;
+mod reduction;
+mod scramble;
+mod svg;
+mod two_phase;
 
 // Re-export modules as if it was in this module.
+pub use algorithm::*;
+pub use beginner::*;
+pub use cfop::*;
 pub use cube::*;
+pub use cubie::*;
+pub use error::Error;
+pub use facelet::*;
 pub use movement::*;
+pub use optimal::*;
+pub use orientation::*;
+pub use piece::*;
+pub use pocket::*;
+pub use reduction::*;
+pub use scramble::*;
+pub use svg::*;
+pub use two_phase::*;
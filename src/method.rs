@@ -0,0 +1,344 @@
+use crate::{
+    coord::is_redundant,
+    cube::Cube,
+    cubie::{face_turn, CubieCube, FACE_TURNS},
+    error::Error,
+    facelet::{Color, Face},
+    movement::{parse_moves, AxisInner, LayerInner, Move},
+};
+
+/// The sides that follow each other around each side when it turns clockwise,
+/// indexed like [`Face::ALL`].
+const AROUND: [[usize; 4]; 6] = [
+    [2, 4, 5, 1],
+    [2, 0, 5, 3],
+    [0, 1, 3, 4],
+    [2, 1, 5, 4],
+    [2, 3, 5, 0],
+    [0, 4, 3, 1],
+];
+
+/// The edges of the cross on the bottom by the side they are under, in the
+/// order of the fronts.
+pub(crate) const CROSS: [usize; 4] = [5, 4, 7, 6];
+
+/// The bottom corners by the side they are to the front right of.
+pub(crate) const BOTTOM_CORNERS: [usize; 4] = [4, 7, 6, 5];
+
+/// The middle layer edges by the side they are to the front right of.
+pub(crate) const MIDDLE_EDGES: [usize; 4] = [8, 11, 10, 9];
+
+/// Which side of the cube each side named in notation is, changed by whole
+/// cube rotations.
+struct Held([usize; 6]);
+
+impl Held {
+    fn turn(&self, face: usize, quarters: usize) -> usize {
+        3 * self.0[face] + (quarters + 3) % 4
+    }
+
+    fn rotate(&mut self, face: usize, quarters: usize) {
+        for _ in 0..quarters {
+            let old = self.0;
+            for (i, &to) in AROUND[face].iter().enumerate() {
+                self.0[to] = old[AROUND[face][(i + 3) % 4]];
+            }
+        }
+    }
+}
+
+/// The face turns of an algorithm for a 3x3x3 written for the front side,
+/// done from the side `front` quarter turns of the cube clockwise from it
+/// instead. Slice turns, wide turns and whole cube rotations are done by
+/// turning the sides and naming the sides differently from then on.
+///
+/// Returns [`Error::InvalidMoveLayer`] for moves of layers a 3x3x3 doesn't
+/// have.
+pub(crate) fn alg(notation: &str, front: usize) -> Result<Vec<usize>, Error> {
+    let mut held = Held([0, 1, 2, 3, 4, 5]);
+    held.rotate(0, front);
+    let mut turns = Vec::new();
+    for mv in parse_moves(notation)? {
+        let face = match mv.axis {
+            AxisInner::Z => 0,
+            AxisInner::Y => 1,
+            AxisInner::X => 2,
+            AxisInner::NegZ => 3,
+            AxisInner::NegY => 4,
+            AxisInner::NegX => 5,
+        };
+        let (opposite, quarters) = ((face + 3) % 6, mv.move_type.quarter_turns() as usize);
+        match mv.affected_range {
+            LayerInner::Single(0) | LayerInner::Multiple(1) => {
+                turns.push(held.turn(face, quarters))
+            }
+            LayerInner::Single(2) => turns.push(held.turn(opposite, 4 - quarters)),
+            LayerInner::Single(1) | LayerInner::Middle => {
+                turns.push(held.turn(face, 4 - quarters));
+                turns.push(held.turn(opposite, quarters));
+                held.rotate(face, quarters);
+            }
+            LayerInner::Multiple(2) => {
+                turns.push(held.turn(opposite, quarters));
+                held.rotate(face, quarters);
+            }
+            LayerInner::Multiple(3) | LayerInner::WholeCube => held.rotate(face, quarters),
+            _ => return Err(Error::InvalidMoveLayer),
+        }
+    }
+    Ok(turns)
+}
+
+/// The face turns undoing others.
+pub(crate) fn inverse(turns: &[usize]) -> Vec<usize> {
+    turns.iter().rev().map(|m| m - m % 3 + 2 - m % 3).collect()
+}
+
+/// No turn, `U`, `U2` and `U'`.
+pub(crate) fn up_turns(quarters: usize) -> Vec<usize> {
+    match quarters % 4 {
+        0 => vec![],
+        q => vec![q - 1],
+    }
+}
+
+pub(crate) fn corner_solved(cube: &CubieCube, corner: usize) -> bool {
+    cube.cp[corner] as usize == corner && cube.co[corner] == 0
+}
+
+pub(crate) fn edge_solved(cube: &CubieCube, edge: usize) -> bool {
+    cube.ep[edge] as usize == edge && cube.eo[edge] == 0
+}
+
+pub(crate) fn first_layer(cube: &CubieCube) -> bool {
+    CROSS.iter().all(|&e| edge_solved(cube, e))
+        && BOTTOM_CORNERS.iter().all(|&k| corner_solved(cube, k))
+}
+
+pub(crate) fn first_two_layers(cube: &CubieCube) -> bool {
+    first_layer(cube) && MIDDLE_EDGES.iter().all(|&e| edge_solved(cube, e))
+}
+
+/// The whole cube rotation bringing the center of a color to the bottom and
+/// a copy of the cube held that way, read as a cubie cube.
+pub(crate) fn hold_down(cube: &Cube, color: Color) -> Result<(Vec<Move>, CubieCube), Error> {
+    if cube.sides() != 3 {
+        return Err(Error::UnsupportedSides {
+            expected: 3,
+            found: cube.sides(),
+        });
+    }
+    let facelets = cube.to_facelets();
+    let face = Face::ALL
+        .iter()
+        .find(|&&face| facelets.get(face, 1, 1) == color)
+        .expect("every color has a center");
+    let rotation = parse_moves(match face {
+        Face::Up => "x2",
+        Face::Right => "z",
+        Face::Front => "x'",
+        Face::Down => "",
+        Face::Left => "z'",
+        Face::Back => "x",
+    })?;
+    let mut held = Cube::from_facelets(3, &facelets.to_string())?;
+    for mv in &rotation {
+        held.rotate(mv)?;
+    }
+    let state = CubieCube::from_cube(&held)?;
+    if !state.is_solvable() {
+        return Err(Error::Unsolvable);
+    }
+    Ok((rotation, state))
+}
+
+/// A cube being solved by hand, one stage at a time.
+pub(crate) struct Solving {
+    pub(crate) cube: CubieCube,
+    turns: Vec<usize>,
+}
+
+impl Solving {
+    pub(crate) fn new(cube: CubieCube) -> Self {
+        Solving {
+            cube,
+            turns: Vec::new(),
+        }
+    }
+
+    /// Make face turns, merging turns of the same side.
+    pub(crate) fn apply(&mut self, turns: &[usize]) {
+        for &m in turns {
+            self.cube = self.cube.turn(m);
+            match self.turns.last() {
+                Some(&last) if last / 3 == m / 3 => {
+                    self.turns.pop();
+                    let quarters = (last % 3 + m % 3 + 2) % 4;
+                    if quarters > 0 {
+                        self.turns.push(3 * (m / 3) + quarters - 1);
+                    }
+                }
+                _ => self.turns.push(m),
+            }
+        }
+    }
+
+    /// The moves made since the last call.
+    pub(crate) fn take(&mut self) -> Vec<Move> {
+        self.turns.drain(..).map(face_turn).collect()
+    }
+
+    /// Make the fewest candidates in a row, at most `most`, that reach the
+    /// goal, choosing the fewest turns among those. Returns whether the goal
+    /// was reached.
+    pub(crate) fn reach(
+        &mut self,
+        candidates: &[Vec<usize>],
+        most: usize,
+        goal: impl Fn(&CubieCube) -> bool,
+    ) -> bool {
+        for count in 0..=most {
+            let mut best = None;
+            combine(
+                self.cube,
+                count,
+                &mut Vec::new(),
+                candidates,
+                &goal,
+                &mut best,
+            );
+            if let Some(turns) = best {
+                self.apply(&turns);
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Make the fewest face turns, at most `most`, that reach the goal.
+    /// Returns whether the goal was reached.
+    pub(crate) fn search(&mut self, most: usize, goal: impl Fn(&CubieCube) -> bool) -> bool {
+        let mut turns = Vec::new();
+        for depth in 0..=most {
+            if search(self.cube, depth, &mut turns, &goal) {
+                self.apply(&turns);
+                return true;
+            }
+        }
+        false
+    }
+}
+
+fn combine(
+    cube: CubieCube,
+    count: usize,
+    turns: &mut Vec<usize>,
+    candidates: &[Vec<usize>],
+    goal: &impl Fn(&CubieCube) -> bool,
+    best: &mut Option<Vec<usize>>,
+) {
+    if count == 0 {
+        if goal(&cube) && best.as_ref().map_or(true, |b| turns.len() < b.len()) {
+            *best = Some(turns.clone());
+        }
+        return;
+    }
+    for candidate in candidates {
+        let len = turns.len();
+        turns.extend_from_slice(candidate);
+        let next = candidate.iter().fold(cube, |c, &m| c.turn(m));
+        combine(next, count - 1, turns, candidates, goal, best);
+        turns.truncate(len);
+    }
+}
+
+fn search(
+    cube: CubieCube,
+    togo: usize,
+    turns: &mut Vec<usize>,
+    goal: &impl Fn(&CubieCube) -> bool,
+) -> bool {
+    if togo == 0 {
+        return goal(&cube);
+    }
+    for m in 0..FACE_TURNS {
+        if is_redundant(turns.last().copied(), m) {
+            continue;
+        }
+        turns.push(m);
+        if search(cube.turn(m), togo - 1, turns, goal) {
+            return true;
+        }
+        turns.pop();
+    }
+    false
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_alg() {
+        assert_eq!(alg("R U R' U'", 0), Ok(vec![3, 0, 5, 2]));
+        // From the right side the right side is the back.
+        assert_eq!(alg("R U F2 D'", 1), Ok(vec![15, 0, 4, 11]));
+        assert_eq!(alg("L B", 2), Ok(vec![3, 6]));
+        // Rotations, slices and wide turns only rename the sides.
+        assert_eq!(
+            alg("x R' U R' D2 R U' R' D2 R2 x'", 0),
+            alg("R' F R' B2 R F' R' B2 R2", 0)
+        );
+        assert_eq!(alg("y R U", 0), alg("R U", 1));
+        assert_eq!(alg("M2 U", 0), alg("L2 R2 D", 0));
+        for notation in [
+            "M2 U M2 U2 M2 U M2",
+            "M' U M2 U M2 U M' U2 M2",
+            "r U R' U' r' F R F'",
+            "F R U R' U' F' f R U R' U' f'",
+            "S R E2 B' E2 S'",
+        ]
+        .iter()
+        {
+            let mut expected = Cube::new3x3x3();
+            expected.apply(&notation.parse().unwrap()).unwrap();
+            let mut cube = Cube::new3x3x3();
+            for &m in alg(notation, 0).unwrap().iter() {
+                cube.rotate(&face_turn(m)).unwrap();
+            }
+            assert_eq!(cube.to_kociemba_string(), expected.to_kociemba_string());
+        }
+        assert_eq!(
+            inverse(&alg("R U2 F'", 0).unwrap()),
+            alg("F U2 R'", 0).unwrap()
+        );
+        assert_eq!(alg("4R", 0), Err(Error::InvalidMoveLayer));
+        assert_eq!(alg("3Rw 4Rw", 0), Err(Error::InvalidMoveLayer));
+        assert!(matches!(alg("R Q", 0), Err(Error::InvalidNotation { .. })));
+        assert_eq!(up_turns(3), vec![2]);
+        assert!(up_turns(4).is_empty());
+    }
+
+    #[test]
+    fn test_apply() {
+        let mut solving = Solving::new(CubieCube::SOLVED);
+        solving.apply(&alg("R U U2 U R' F2 F", 0).unwrap());
+        assert_eq!(solving.take(), parse_moves("F'").unwrap());
+        assert_eq!(solving.cube, CubieCube::SOLVED.turn(8));
+        assert!(solving.take().is_empty());
+    }
+
+    #[test]
+    fn test_reach() {
+        let mut solving = Solving::new(CubieCube::SOLVED);
+        solving.apply(&alg("R U R' U'", 0).unwrap());
+        solving.take();
+        let sexy: Vec<Vec<usize>> = (0..4).map(|f| alg("R U R' U'", f).unwrap()).collect();
+        assert!(!solving.reach(&sexy, 1, CubieCube::is_solved));
+        assert!(solving.reach(&sexy, 5, CubieCube::is_solved));
+        assert!(solving.cube.is_solved());
+        solving.take();
+        assert!(solving.search(3, |c| edge_solved(c, 0) && c.cp[0] != 0));
+        assert_eq!(solving.take().len(), 1);
+    }
+}
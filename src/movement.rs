@@ -1,15 +1,36 @@
-use std::convert::{TryFrom, TryInto};
+use std::{
+    convert::{TryFrom, TryInto},
+    fmt,
+    str::FromStr,
+};
 
 use nalgebra::Matrix3;
 
 use crate::error::Error;
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub(crate) enum LayerInner {
     Single(usize),
     Multiple(usize),
+    /// The middle layer of a cube with an odd number of sides, turned by `M`,
+    /// `E` and `S`.
+    Middle,
     WholeCube,
 }
 
+impl LayerInner {
+    /// The layers turned on a cube with a number of sides, with the middle
+    /// layer as a single layer. Cubes with an even number of sides have no
+    /// middle layer.
+    pub(crate) fn for_sides(self, sides: usize) -> Result<LayerInner, Error> {
+        match self {
+            LayerInner::Middle if sides % 2 == 1 => Ok(LayerInner::Single(sides / 2)),
+            LayerInner::Middle => Err(Error::InvalidMoveLayer),
+            layer => Ok(layer),
+        }
+    }
+}
+
 impl From<Layer> for LayerInner {
     fn from(l: Layer) -> Self {
         match l {
@@ -19,7 +40,7 @@ impl From<Layer> for LayerInner {
     }
 }
 
-#[derive(Clone)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub(crate) enum AxisInner {
     X,
     NegX,
@@ -29,6 +50,39 @@ pub(crate) enum AxisInner {
     NegZ,
 }
 
+impl AxisInner {
+    /// The axis pointing the opposite direction.
+    pub(crate) fn negate(self) -> Self {
+        match self {
+            AxisInner::X => AxisInner::NegX,
+            AxisInner::NegX => AxisInner::X,
+            AxisInner::Y => AxisInner::NegY,
+            AxisInner::NegY => AxisInner::Y,
+            AxisInner::Z => AxisInner::NegZ,
+            AxisInner::NegZ => AxisInner::Z,
+        }
+    }
+
+    pub(crate) fn is_negative(self) -> bool {
+        matches!(self, AxisInner::NegX | AxisInner::NegY | AxisInner::NegZ)
+    }
+
+    /// Whether `M`, `E` or `S` turn around the axis, pointing out of the side
+    /// they follow.
+    pub(crate) fn is_middle_axis(self) -> bool {
+        matches!(self, AxisInner::NegY | AxisInner::NegZ | AxisInner::X)
+    }
+
+    /// The axis ignoring which direction it points.
+    pub(crate) fn unsigned(self) -> Axis {
+        match self {
+            AxisInner::X | AxisInner::NegX => Axis::X,
+            AxisInner::Y | AxisInner::NegY => Axis::Y,
+            AxisInner::Z | AxisInner::NegZ => Axis::Z,
+        }
+    }
+}
+
 impl From<Axis> for AxisInner {
     fn from(a: Axis) -> Self {
         match a {
@@ -40,6 +94,8 @@ impl From<Axis> for AxisInner {
 }
 
 /// Which layer(s) to affect when making the move
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Layer {
     /// Affect a single layer (indexed at 0)
     Single(usize),
@@ -48,13 +104,40 @@ pub enum Layer {
     Multiple(usize),
 }
 
+impl fmt::Display for Layer {
+    /// Write the layer as the prefix used in notation, `2` for the second layer
+    /// and `3w` for the first three layers.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Layer::Single(l) => write!(f, "{}", l + 1),
+            Layer::Multiple(l) => write!(f, "{}w", l),
+        }
+    }
+}
+
 /// Axis to rotate cube around
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Axis {
     X,
     Y,
     Z,
 }
 
+impl fmt::Display for Axis {
+    /// Write the axis as the letter of the whole cube rotation around it.
+    ///
+    /// Notation names rotations after the face they follow, so `X` (through the
+    /// front) is `z`, `Y` (through the right) is `x` and `Z` (through the top) is `y`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Axis::X => write!(f, "z"),
+            Axis::Y => write!(f, "x"),
+            Axis::Z => write!(f, "y"),
+        }
+    }
+}
+
 impl TryFrom<AxisInner> for Axis {
     type Error = Error;
 
@@ -69,7 +152,8 @@ impl TryFrom<AxisInner> for Axis {
 }
 
 /// What type of move to do.
-#[derive(Clone)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum MoveType {
     /// Rotate clockwise
     Clockwise,
@@ -79,8 +163,39 @@ pub enum MoveType {
     Twice,
 }
 
+impl fmt::Display for MoveType {
+    /// Write the suffix used in notation for the move type.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MoveType::Clockwise => Ok(()),
+            MoveType::CounterClockwise => write!(f, "'"),
+            MoveType::Twice => write!(f, "2"),
+        }
+    }
+}
+
 impl MoveType {
-    fn opposite(&self) -> Self {
+    /// Number of clockwise quarter turns the move type is equal to.
+    pub(crate) fn quarter_turns(&self) -> u8 {
+        match self {
+            MoveType::Clockwise => 1,
+            MoveType::Twice => 2,
+            MoveType::CounterClockwise => 3,
+        }
+    }
+
+    /// The move type equal to a number of clockwise quarter turns, if any turning is needed.
+    pub(crate) fn from_quarter_turns(turns: u8) -> Option<Self> {
+        match turns % 4 {
+            1 => Some(MoveType::Clockwise),
+            2 => Some(MoveType::Twice),
+            3 => Some(MoveType::CounterClockwise),
+            _ => None,
+        }
+    }
+
+    /// The move type that undoes this one.
+    pub fn opposite(&self) -> Self {
         match self {
             MoveType::Clockwise => MoveType::CounterClockwise,
             MoveType::CounterClockwise => MoveType::Clockwise,
@@ -89,9 +204,21 @@ impl MoveType {
     }
 }
 
+/// Plane to mirror moves across, named after the slice that lies in it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Plane {
+    /// The plane between the left and right sides.
+    M,
+    /// The plane between the top and bottom sides.
+    E,
+    /// The plane between the front and back sides.
+    S,
+}
+
 /// Describe how to move the cube.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct Move {
-    move_type: MoveType,
+    pub(crate) move_type: MoveType,
     pub(crate) axis: AxisInner,
     pub(crate) affected_range: LayerInner,
 }
@@ -160,6 +287,58 @@ impl Move {
         }
     }
 
+    /// The move that undoes this one.
+    pub fn inverse(&self) -> Self {
+        Move {
+            move_type: self.move_type.opposite(),
+            ..*self
+        }
+    }
+
+    /// The move as it would be done in a mirror placed on the given plane.
+    ///
+    /// Mirroring swaps the sides on either side of the plane and reverses the
+    /// direction of every turn, so `R` mirrored on [`Plane::M`] becomes `L'`.
+    pub fn mirror(&self, plane: Plane) -> Self {
+        let axis = match (plane, self.axis) {
+            (Plane::S, AxisInner::X)
+            | (Plane::S, AxisInner::NegX)
+            | (Plane::M, AxisInner::Y)
+            | (Plane::M, AxisInner::NegY)
+            | (Plane::E, AxisInner::Z)
+            | (Plane::E, AxisInner::NegZ) => self.axis.negate(),
+            _ => self.axis,
+        };
+        let move_type = self.move_type.opposite();
+        match self.affected_range {
+            // Whole cube rotations are always kept around a positive axis.
+            LayerInner::WholeCube if axis.is_negative() => Move {
+                move_type: move_type.opposite(),
+                axis: axis.negate(),
+                affected_range: LayerInner::WholeCube,
+            },
+            // And middle layers like `M`, `E` and `S`.
+            LayerInner::Middle if !axis.is_middle_axis() => Move {
+                move_type: move_type.opposite(),
+                axis: axis.negate(),
+                affected_range: LayerInner::Middle,
+            },
+            _ => Move {
+                move_type,
+                axis,
+                affected_range: self.affected_range,
+            },
+        }
+    }
+
+    /// Display the move using the given notation dialect.
+    pub fn notation(&self, notation: Notation) -> MoveNotation {
+        MoveNotation {
+            mv: *self,
+            notation,
+        }
+    }
+
     pub(crate) fn rotation_matrix(&self) -> &Matrix3<isize> {
         match self.normalize_axis_move_type() {
             (Axis::X, MoveType::Clockwise) => &ROT_MAT_X_CW,
@@ -181,15 +360,219 @@ impl Move {
             AxisInner::NegZ => (Axis::Z, self.move_type.opposite()),
             AxisInner::X | AxisInner::Y | AxisInner::Z => (
                 self.axis
-                    .clone()
                     .try_into()
                     .expect("dealing with values only supported by axis"),
-                self.move_type.clone(),
+                self.move_type,
             ),
         }
     }
 }
 
+impl fmt::Display for Move {
+    /// Write the move in WCA notation.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.notation(Notation::Wca).fmt(f)
+    }
+}
+
+impl FromStr for Move {
+    type Err = Error;
+
+    /// Parse a single move written in WCA or SiGN notation.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_token(s).ok_or_else(|| Error::InvalidNotation {
+            offset: 0,
+            token: s.to_string(),
+        })
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for Move {
+    /// Write the move as its WCA notation, like `Rw'` or `x2`.
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Move {
+    /// Read a move written in WCA or SiGN notation.
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let notation = String::deserialize(deserializer)?;
+        notation.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+/// Dialect to use when writing moves that affect more than the outer layer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Notation {
+    /// Wide moves are written with a `w` suffix, `Rw` and `3Rw`.
+    #[default]
+    Wca,
+    /// Wide moves are written in lower case, `r` and `3r`.
+    Sign,
+}
+
+/// A move paired with the dialect to display it in, created with [`Move::notation`].
+pub struct MoveNotation {
+    mv: Move,
+    notation: Notation,
+}
+
+impl fmt::Display for MoveNotation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Move {
+            move_type,
+            axis,
+            affected_range,
+        } = self.mv;
+        if let LayerInner::WholeCube = affected_range {
+            let (axis, move_type) = self.mv.normalize_axis_move_type();
+            return write!(f, "{}{}", axis, move_type);
+        }
+        if let LayerInner::Middle = affected_range {
+            let (axis, move_type) = if axis.is_middle_axis() {
+                (axis, move_type)
+            } else {
+                (axis.negate(), move_type.opposite())
+            };
+            let slice = match axis.unsigned() {
+                Axis::X => 'S',
+                Axis::Y => 'M',
+                Axis::Z => 'E',
+            };
+            return write!(f, "{}{}", slice, move_type);
+        }
+        let face = match axis {
+            AxisInner::X => 'F',
+            AxisInner::NegX => 'B',
+            AxisInner::Y => 'R',
+            AxisInner::NegY => 'L',
+            AxisInner::Z => 'U',
+            AxisInner::NegZ => 'D',
+        };
+        match (affected_range, self.notation) {
+            (LayerInner::Single(0), _) => write!(f, "{}", face)?,
+            (LayerInner::Single(l), _) => write!(f, "{}{}", l + 1, face)?,
+            (LayerInner::Multiple(2), Notation::Wca) => write!(f, "{}w", face)?,
+            (LayerInner::Multiple(l), Notation::Wca) => write!(f, "{}{}w", l, face)?,
+            (LayerInner::Multiple(2), Notation::Sign) => {
+                write!(f, "{}", face.to_ascii_lowercase())?
+            }
+            (LayerInner::Multiple(l), Notation::Sign) => {
+                write!(f, "{}{}", l, face.to_ascii_lowercase())?
+            }
+            (LayerInner::WholeCube, _) | (LayerInner::Middle, _) => {
+                unreachable!("whole cube rotations and middle layers are handled above")
+            }
+        }
+        write!(f, "{}", move_type)
+    }
+}
+
+/// Write a sequence of moves separated by spaces using the given notation dialect.
+pub fn format_moves(moves: &[Move], notation: Notation) -> String {
+    moves
+        .iter()
+        .map(|mv| mv.notation(notation).to_string())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Parse a whitespace separated sequence of moves written in WCA or SiGN notation.
+///
+/// Supported tokens are:
+/// * Face turns `R`, `L`, `U`, `D`, `F` and `B`.
+/// * Numbered slice turns such as `2R` or `3U` which turn only that layer.
+/// * Wide turns `Rw`/`r` (two layers) and `3Rw`/`3r` (three layers), `0Rw`
+///   turns no layers.
+/// * Slice turns `M`, `E` and `S` which turn the middle layer like `L`, `D` and
+///   `F`, only on cubes with an odd number of sides.
+/// * Whole cube rotations `x`, `y` and `z`.
+///
+/// Each of these may be followed by `2` (or `2'`) to turn twice and `'` to turn
+/// counter-clockwise.
+pub fn parse_moves(notation: &str) -> Result<Vec<Move>, Error> {
+    let mut moves = Vec::new();
+    let mut start = None;
+    for (i, c) in notation.char_indices().chain(Some((notation.len(), ' '))) {
+        match (c.is_whitespace(), start) {
+            (false, None) => start = Some(i),
+            (true, Some(s)) => {
+                let token = &notation[s..i];
+                let mv = parse_token(token).ok_or_else(|| Error::InvalidNotation {
+                    offset: s,
+                    token: token.to_string(),
+                })?;
+                moves.push(mv);
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    Ok(moves)
+}
+
+fn parse_token(token: &str) -> Option<Move> {
+    let digits = token.find(|c: char| !c.is_ascii_digit())?;
+    let (prefix, rest) = token.split_at(digits);
+    let prefix = if prefix.is_empty() {
+        None
+    } else {
+        Some(prefix.parse::<usize>().ok()?)
+    };
+
+    let mut chars = rest.chars();
+    let face = chars.next()?;
+    let mut rest = chars.as_str();
+    let wide = if face.is_ascii_uppercase() && rest.starts_with('w') {
+        rest = &rest[1..];
+        true
+    } else {
+        false
+    };
+    let move_type = match rest {
+        "" => MoveType::Clockwise,
+        "'" => MoveType::CounterClockwise,
+        "2" | "2'" => MoveType::Twice,
+        _ => return None,
+    };
+
+    Some(match face {
+        'x' | 'y' | 'z' | 'M' | 'E' | 'S' if prefix.is_some() || wide => return None,
+        'x' => Move::rotate_cube(Axis::Y, move_type),
+        'y' => Move::rotate_cube(Axis::Z, move_type),
+        'z' => Move::rotate_cube(Axis::X, move_type),
+        'M' | 'E' | 'S' => Move {
+            move_type,
+            axis: match face {
+                'M' => AxisInner::NegY,
+                'E' => AxisInner::NegZ,
+                _ => AxisInner::X,
+            },
+            affected_range: LayerInner::Middle,
+        },
+        _ => {
+            // Wide turns of no layers are written `0Rw`, there is no layer `0R`.
+            let layer = if wide || face.is_ascii_lowercase() {
+                Layer::Multiple(prefix.unwrap_or(2))
+            } else {
+                Layer::Single(prefix.unwrap_or(1).checked_sub(1)?)
+            };
+            match face.to_ascii_uppercase() {
+                'R' => Move::rotate_right(layer, move_type),
+                'L' => Move::rotate_left(layer, move_type),
+                'U' => Move::rotate_top(layer, move_type),
+                'D' => Move::rotate_bottom(layer, move_type),
+                'F' => Move::rotate_front(layer, move_type),
+                'B' => Move::rotate_back(layer, move_type),
+                _ => return None,
+            }
+        }
+    })
+}
+
 static ROT_MAT_Z_CW: Matrix3<isize> = Matrix3::new(0, 1, 0, -1, 0, 0, 0, 0, 1);
 static ROT_MAT_Z_CCW: Matrix3<isize> = Matrix3::new(0, -1, 0, 1, 0, 0, 0, 0, 1);
 static ROT_MAT_Z_2: Matrix3<isize> = Matrix3::new(-1, 0, 0, 0, -1, 0, 0, 0, 1);
@@ -289,4 +672,208 @@ mod test {
         assert_eq!(&ROT_MAT_X_2, back_2.rotation_matrix());
         assert_eq!(&ROT_MAT_X_2, cube_x_2.rotation_matrix());
     }
+
+    #[test]
+    fn test_parse_moves() {
+        let middle = |axis| Move {
+            move_type: MoveType::Clockwise,
+            axis,
+            affected_range: LayerInner::Middle,
+        };
+        let moves = parse_moves("R U R' U2 Rw 3Fw' x y2 M E S").unwrap();
+        assert_eq!(
+            moves,
+            vec![
+                Move::rotate_right(Layer::Single(0), MoveType::Clockwise),
+                Move::rotate_top(Layer::Single(0), MoveType::Clockwise),
+                Move::rotate_right(Layer::Single(0), MoveType::CounterClockwise),
+                Move::rotate_top(Layer::Single(0), MoveType::Twice),
+                Move::rotate_right(Layer::Multiple(2), MoveType::Clockwise),
+                Move::rotate_front(Layer::Multiple(3), MoveType::CounterClockwise),
+                Move::rotate_cube(Axis::Y, MoveType::Clockwise),
+                Move::rotate_cube(Axis::Z, MoveType::Twice),
+                middle(AxisInner::NegY),
+                middle(AxisInner::NegZ),
+                middle(AxisInner::X),
+            ]
+        );
+        assert_ne!(
+            moves[8],
+            Move::rotate_left(Layer::Single(1), MoveType::Clockwise)
+        );
+    }
+
+    #[test]
+    fn test_parse_moves_sign() {
+        let moves = parse_moves("  r 3l'\tu2' 2D 4B2 z'  ").unwrap();
+        assert_eq!(
+            moves,
+            vec![
+                Move::rotate_right(Layer::Multiple(2), MoveType::Clockwise),
+                Move::rotate_left(Layer::Multiple(3), MoveType::CounterClockwise),
+                Move::rotate_top(Layer::Multiple(2), MoveType::Twice),
+                Move::rotate_bottom(Layer::Single(1), MoveType::Clockwise),
+                Move::rotate_back(Layer::Single(3), MoveType::Twice),
+                Move::rotate_cube(Axis::X, MoveType::CounterClockwise),
+            ]
+        );
+        assert_eq!(parse_moves("").unwrap(), vec![]);
+        assert_eq!(
+            "F'".parse::<Move>().unwrap(),
+            Move::rotate_front(Layer::Single(0), MoveType::CounterClockwise)
+        );
+    }
+
+    #[test]
+    fn test_parse_moves_invalid() {
+        for (notation, offset, token) in [
+            ("R U Q", 4, "Q"),
+            ("R 0U", 2, "0U"),
+            ("0x", 0, "0x"),
+            ("R2 2x", 3, "2x"),
+            ("Mw", 0, "Mw"),
+            ("R U3", 2, "U3"),
+            ("R' F''", 3, "F''"),
+            ("rw", 0, "rw"),
+            ("12", 0, "12"),
+        ]
+        .iter()
+        {
+            assert_eq!(
+                parse_moves(notation),
+                Err(Error::InvalidNotation {
+                    offset: *offset,
+                    token: token.to_string()
+                }),
+                "parsing {:?}",
+                notation
+            );
+        }
+        assert_eq!(
+            "R U".parse::<Move>(),
+            Err(Error::InvalidNotation {
+                offset: 0,
+                token: "R U".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn test_display() {
+        let moves = parse_moves("R' 2U2 3Rw y' r 3b2 x M E S z2").unwrap();
+        assert_eq!(
+            format_moves(&moves, Notation::Wca),
+            "R' 2U2 3Rw y' Rw 3Bw2 x M E S z2"
+        );
+        assert_eq!(
+            format_moves(&moves, Notation::Sign),
+            "R' 2U2 3r y' r 3b2 x M E S z2"
+        );
+        assert_eq!(moves[2].to_string(), "3Rw");
+        assert_eq!(Layer::Single(1).to_string(), "2");
+        assert_eq!(Layer::Multiple(3).to_string(), "3w");
+        let none = Move::rotate_right(Layer::Multiple(0), MoveType::Clockwise);
+        assert_eq!(none.to_string(), "0Rw");
+        assert_eq!("0Rw".parse::<Move>().unwrap(), none);
+        assert_eq!("0r".parse::<Move>().unwrap(), none);
+        assert_eq!(Axis::X.to_string(), "z");
+        assert_eq!(MoveType::CounterClockwise.to_string(), "'");
+    }
+
+    #[test]
+    fn test_display_round_trip() {
+        let faces: [fn(Layer, MoveType) -> Move; 6] = [
+            Move::rotate_top,
+            Move::rotate_bottom,
+            Move::rotate_left,
+            Move::rotate_right,
+            Move::rotate_front,
+            Move::rotate_back,
+        ];
+        let move_types = [
+            MoveType::Clockwise,
+            MoveType::CounterClockwise,
+            MoveType::Twice,
+        ];
+        let mut moves = Vec::new();
+        for face in faces.iter() {
+            for l in 0..=6 {
+                for mt in move_types.iter() {
+                    moves.push(face(Layer::Single(l), *mt));
+                    moves.push(face(Layer::Multiple(l), *mt));
+                }
+            }
+        }
+        for axis in [Axis::X, Axis::Y, Axis::Z].iter() {
+            for mt in move_types.iter() {
+                moves.push(Move::rotate_cube(*axis, *mt));
+            }
+        }
+        for axis in [AxisInner::X, AxisInner::NegY, AxisInner::NegZ].iter() {
+            for mt in move_types.iter() {
+                moves.push(Move {
+                    move_type: *mt,
+                    axis: *axis,
+                    affected_range: LayerInner::Middle,
+                });
+            }
+        }
+        for notation in [Notation::Wca, Notation::Sign].iter() {
+            for mv in moves.iter() {
+                let written = mv.notation(*notation).to_string();
+                assert_eq!(&written.parse::<Move>().unwrap(), mv, "{}", written);
+            }
+            let written = format_moves(&moves, *notation);
+            assert_eq!(parse_moves(&written).unwrap(), moves);
+        }
+    }
+
+    #[test]
+    fn test_inverse() {
+        let moves = parse_moves("R U' F2 3Lw' x y' z2 M").unwrap();
+        let inverse: Vec<Move> = moves.iter().map(Move::inverse).collect();
+        assert_eq!(inverse, parse_moves("R' U F2 3Lw x' y z2 M'").unwrap());
+    }
+
+    #[test]
+    fn test_mirror() {
+        let moves = parse_moves("R U' F2 3Lw' x y' z2 M").unwrap();
+        let mirror = |plane| moves.iter().map(|mv| mv.mirror(plane)).collect::<Vec<_>>();
+        assert_eq!(
+            mirror(Plane::M),
+            parse_moves("L' U F2 3Rw x y z2 M").unwrap()
+        );
+        assert_eq!(
+            mirror(Plane::E),
+            parse_moves("R' D F2 3Lw x' y' z2 M'").unwrap()
+        );
+        assert_eq!(
+            mirror(Plane::S),
+            parse_moves("R' U B2 3Lw x' y z2 M'").unwrap()
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde() {
+        let moves = parse_moves("R U' 3Fw2 2L x' M").unwrap();
+        let json = serde_json::to_string(&moves).unwrap();
+        assert_eq!(json, r#"["R","U'","3Fw2","2L","x'","M"]"#);
+        assert_eq!(serde_json::from_str::<Vec<Move>>(&json).unwrap(), moves);
+        assert_eq!(
+            serde_json::from_str::<Move>(r#""r2""#).unwrap(),
+            "2Rw2".parse().unwrap()
+        );
+        assert!(serde_json::from_str::<Move>(r#""Q""#).is_err());
+
+        assert_eq!(
+            serde_json::to_string(&Layer::Multiple(2)).unwrap(),
+            r#"{"Multiple":2}"#
+        );
+        assert_eq!(serde_json::to_string(&Axis::Y).unwrap(), r#""Y""#);
+        assert_eq!(
+            serde_json::from_str::<MoveType>(r#""Twice""#).unwrap(),
+            MoveType::Twice
+        );
+    }
 }
@@ -0,0 +1,982 @@
+use crate::{
+    coord::{is_redundant, move_tables, CORNERS, TWISTS},
+    cube::Cube,
+    cubie::{face_turn, rotation_table, CubieCube, FACE_TURNS},
+    error::Error,
+    facelet::Face,
+    movement::Move,
+};
+use std::{
+    fmt,
+    fs::File,
+    io::{self, BufReader, BufWriter, Read, Write},
+    path::Path,
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Mutex, OnceLock,
+    },
+    thread,
+};
+
+/// The edges of the stored edge database, `UR`, `UL`, `UF`, `UB`, `FR` and
+/// `BL`. A half turn of the whole cube around the up side keeps them among
+/// themselves, and one around the right side swaps them with the other six.
+const EDGES: [u8; 6] = [0, 2, 1, 3, 8, 10];
+
+/// A rotation of the whole cube, to see a cube from another side.
+#[derive(Debug, Clone)]
+struct Symmetry {
+    rotation: CubieCube,
+    inverse: CubieCube,
+    /// The face turn each face turn is when seen through the rotation.
+    turns: [usize; FACE_TURNS],
+}
+
+impl Symmetry {
+    fn new(rotation: CubieCube) -> Self {
+        let mut symmetry = Symmetry {
+            rotation,
+            inverse: rotation.inverse(),
+            turns: [0; FACE_TURNS],
+        };
+        for m in 0..FACE_TURNS {
+            let seen = symmetry.conjugate(&CubieCube::SOLVED.turn(m));
+            symmetry.turns[m] = (0..FACE_TURNS)
+                .find(|&t| CubieCube::SOLVED.turn(t) == seen)
+                .expect("rotations see face turns as face turns");
+        }
+        symmetry
+    }
+
+    /// The cube seen through the rotation, which takes as many turns to solve.
+    fn conjugate(&self, cube: &CubieCube) -> CubieCube {
+        self.inverse.multiply(cube).multiply(&self.rotation)
+    }
+}
+
+/// Clockwise quarter turns of the whole cube about a side.
+fn rotation(face: Face, quarters: usize) -> CubieCube {
+    let turn = &rotation_table()[face as usize];
+    (0..quarters).fold(CubieCube::SOLVED, |cube, _| cube.multiply(turn))
+}
+
+/// The number of ways the edge database is looked up in a search.
+const LOOKUPS: usize = 6;
+
+/// The rotations the edges of a cube are seen through to look them up in the
+/// edge database. Each pair sees all twelve edges, and the three pairs are
+/// turned about the corner `URF` from each other so the stored edges sit in
+/// the up, right and front layers in turn.
+fn lookups() -> &'static [Symmetry] {
+    static SYMMETRIES: OnceLock<Vec<Symmetry>> = OnceLock::new();
+    SYMMETRIES.get_or_init(|| {
+        let x2 = rotation(Face::Right, 2);
+        // Turning the front side up and then the right side to the front
+        // keeps the corner `URF` in its place.
+        let corner = rotation(Face::Right, 1).multiply(&rotation(Face::Up, 1));
+        let mut lookups = Vec::with_capacity(LOOKUPS);
+        let mut turned = CubieCube::SOLVED;
+        for _ in 0..LOOKUPS / 2 {
+            lookups.push(Symmetry::new(turned));
+            lookups.push(Symmetry::new(turned.multiply(&x2)));
+            turned = turned.multiply(&corner);
+        }
+        lookups
+    })
+}
+
+/// A coordinate stored by symmetry class. The values the symmetries turn
+/// each other into are a class, represented by the smallest of them.
+struct SymCoordinate {
+    /// The class of each value and the symmetry turning it into the
+    /// representative of the class, as `class << 4 | symmetry`, with bit `3`
+    /// set if symmetries other than the identity keep the representative.
+    classes: Vec<u32>,
+    representatives: Vec<u32>,
+    /// The symmetries keeping each representative, one bit each.
+    stabilizers: Vec<u8>,
+}
+
+/// Marks a class kept by some symmetries, see [`SymCoordinate::classes`].
+const KEPT: u32 = 1 << 3;
+
+impl SymCoordinate {
+    /// Sort `size` values into classes, where `conjugate(value, s)` is the
+    /// value seen through symmetry `s`, one of at most eight.
+    fn new(
+        size: usize,
+        symmetries: &[Symmetry],
+        conjugate: impl Fn(usize, usize) -> usize,
+    ) -> Self {
+        let inverses: Vec<usize> = symmetries
+            .iter()
+            .map(|s| {
+                symmetries
+                    .iter()
+                    .position(|t| t.rotation == s.inverse)
+                    .expect("the symmetries are a group")
+            })
+            .collect();
+        let mut classes = vec![u32::MAX; size];
+        let (mut representatives, mut stabilizers) = (Vec::new(), Vec::new());
+        for value in 0..size {
+            if classes[value] != u32::MAX {
+                continue;
+            }
+            let class = representatives.len() as u32;
+            let seen: Vec<usize> = (0..symmetries.len()).map(|s| conjugate(value, s)).collect();
+            let stabilizer = seen
+                .iter()
+                .enumerate()
+                .fold(0, |kept, (s, &v)| kept | u8::from(v == value) << s);
+            let kept = if stabilizer == 1 { 0 } else { KEPT };
+            for (&v, &inverse) in seen.iter().zip(inverses.iter()) {
+                classes[v] = class << 4 | kept | inverse as u32;
+            }
+            representatives.push(value as u32);
+            stabilizers.push(stabilizer);
+        }
+        SymCoordinate {
+            classes,
+            representatives,
+            stabilizers,
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.representatives.len()
+    }
+
+    fn representative(&self, class: usize) -> usize {
+        self.representatives[class] as usize
+    }
+
+    /// The class of a value, and another coordinate seen through the
+    /// symmetry turning the value into the representative, where
+    /// `conjugates[s]` sees the other coordinate through symmetry `s`.
+    ///
+    /// A representative kept by some symmetries is seen the same through
+    /// them, so the smallest of the other coordinates they give is taken to
+    /// store each state only once.
+    fn reduce(&self, value: usize, other: usize, conjugates: &[Vec<u16>]) -> (usize, usize) {
+        let c = self.classes[value];
+        let class = (c >> 4) as usize;
+        let other = conjugates[(c & 7) as usize][other] as usize;
+        if c & KEPT == 0 {
+            return (class, other);
+        }
+        let stabilizer = self.stabilizers[class];
+        let smallest = (0..conjugates.len())
+            .filter(|t| stabilizer >> t & 1 == 1)
+            .map(|t| conjugates[t][other] as usize)
+            .min()
+            .expect("every representative is kept by the identity");
+        (class, smallest)
+    }
+}
+
+/// The corner orders stored by symmetry, and the twists seen through each
+/// symmetry.
+struct CornerSymmetries {
+    orders: SymCoordinate,
+    twists: Vec<Vec<u16>>,
+}
+
+impl CornerSymmetries {
+    /// The index in the corner database of a corner order and twist.
+    fn index(&self, order: usize, twist: usize) -> usize {
+        let (class, twist) = self.orders.reduce(order, twist, &self.twists);
+        class * TWISTS + twist
+    }
+}
+
+/// The eight rotations keeping the up and down sides on the up-down axis,
+/// which never twist a corner.
+fn ud_rotations() -> Vec<Symmetry> {
+    let x2 = rotation(Face::Right, 2);
+    (0..4)
+        .flat_map(|q| {
+            let y = rotation(Face::Up, q);
+            vec![Symmetry::new(y), Symmetry::new(y.multiply(&x2))]
+        })
+        .collect()
+}
+
+/// The corner symmetries, see [`ud_rotations`], made the first time they are
+/// needed.
+fn corner_symmetries() -> &'static CornerSymmetries {
+    static SYMMETRIES: OnceLock<CornerSymmetries> = OnceLock::new();
+    SYMMETRIES.get_or_init(|| {
+        let symmetries = ud_rotations();
+        let orders = SymCoordinate::new(CORNERS, &symmetries, |order, s| {
+            let mut cube = CubieCube::SOLVED;
+            cube.set_corners(order);
+            symmetries[s].conjugate(&cube).corners()
+        });
+        let twists = symmetries
+            .iter()
+            .map(|symmetry| {
+                (0..TWISTS)
+                    .map(|twist| {
+                        let mut cube = CubieCube::SOLVED;
+                        cube.set_twist(twist);
+                        symmetry.conjugate(&cube).twist() as u16
+                    })
+                    .collect()
+            })
+            .collect();
+        CornerSymmetries { orders, twists }
+    })
+}
+
+/// Marks a state whose distance isn't known yet.
+const UNKNOWN: u8 = 0xf;
+
+/// Distances to solved packed two to a byte.
+#[derive(Clone, PartialEq, Eq)]
+pub(crate) struct Nibbles {
+    len: usize,
+    bytes: Vec<u8>,
+}
+
+impl Nibbles {
+    fn new(len: usize) -> Self {
+        Nibbles {
+            len,
+            bytes: vec![0xff; (len + 1) / 2],
+        }
+    }
+
+    pub(crate) fn get(&self, i: usize) -> u8 {
+        (self.bytes[i / 2] >> (4 * (i % 2))) & 0xf
+    }
+
+    fn set(&mut self, i: usize, value: u8) {
+        let shift = 4 * (i % 2);
+        let byte = &mut self.bytes[i / 2];
+        *byte = (*byte & !(0xf << shift)) | (value << shift);
+    }
+
+    /// The fewest turns from every state to `solved`, where `neighbour` gives
+    /// the state reached by each face turn.
+    fn distances(len: usize, solved: usize, neighbour: impl Fn(usize, usize) -> usize) -> Self {
+        let mut table = Nibbles::new(len);
+        table.set(solved, 0);
+        let (mut filled, mut depth) = (1, 0);
+        while filled < len {
+            let before = filled;
+            // Once most states are known it is faster to look for the unknown
+            // ones next to the last found than to go forward from those.
+            let backward = filled > len / 2;
+            for i in 0..len {
+                if backward {
+                    if table.get(i) == UNKNOWN
+                        && (0..FACE_TURNS).any(|m| table.get(neighbour(i, m)) == depth)
+                    {
+                        table.set(i, depth + 1);
+                        filled += 1;
+                    }
+                } else if table.get(i) == depth {
+                    for m in 0..FACE_TURNS {
+                        let to = neighbour(i, m);
+                        if table.get(to) == UNKNOWN {
+                            table.set(to, depth + 1);
+                            filled += 1;
+                        }
+                    }
+                }
+            }
+            if filled == before {
+                break;
+            }
+            depth += 1;
+        }
+        table
+    }
+}
+
+/// Where a set of edges go after every face turn, and how they are stored by
+/// symmetry.
+pub(crate) struct EdgeMoves {
+    /// The edges followed, in the order of their places and flips.
+    pieces: Vec<u8>,
+    edges: usize,
+    /// The next placement of the edges, as in [`EdgeMoves::placement`].
+    places: Vec<[u32; FACE_TURNS]>,
+    /// The edges flipped by the turn, one bit per edge.
+    flips: Vec<[u8; FACE_TURNS]>,
+    /// The placements stored by the rotations keeping the edges among
+    /// themselves without flipping any.
+    classes: SymCoordinate,
+    /// The flips seen through each of those rotations.
+    class_flips: Vec<Vec<u16>>,
+}
+
+impl EdgeMoves {
+    pub(crate) fn new(pieces: &[u8]) -> Self {
+        let edges = pieces.len();
+        let turns: Vec<CubieCube> = (0..FACE_TURNS).map(|m| CubieCube::SOLVED.turn(m)).collect();
+        // Where the edge at each place goes after each turn.
+        let mut to = [[0; 12]; FACE_TURNS];
+        for (m, turn) in turns.iter().enumerate() {
+            for (place, &from) in turn.ep.iter().enumerate() {
+                to[m][from as usize] = place;
+            }
+        }
+        let count = (0..edges).map(|k| 12 - k).product();
+        let mut moves = EdgeMoves {
+            pieces: pieces.to_vec(),
+            edges,
+            places: Vec::with_capacity(count),
+            flips: Vec::with_capacity(count),
+            // Filled in once the placements can be worked out.
+            classes: SymCoordinate::new(0, &[], |v, _| v),
+            class_flips: Vec::new(),
+        };
+        for index in 0..count {
+            let places = moves.places_of(index);
+            let (mut next, mut flips) = ([0; FACE_TURNS], [0; FACE_TURNS]);
+            for m in 0..FACE_TURNS {
+                let moved: Vec<u8> = places.iter().map(|&p| to[m][p as usize] as u8).collect();
+                next[m] = moves.placement(&moved) as u32;
+                flips[m] = moved
+                    .iter()
+                    .enumerate()
+                    .fold(0, |f, (k, &p)| f | turns[m].eo[p as usize] << k);
+            }
+            moves.places.push(next);
+            moves.flips.push(flips);
+        }
+
+        // Through these the flips of the edges only change places, so they
+        // can be seen through them whatever the placement.
+        let symmetries: Vec<Symmetry> = [CubieCube::SOLVED, rotation(Face::Up, 2)]
+            .iter()
+            .filter(|r| {
+                r.eo.iter().all(|&o| o == 0)
+                    && pieces.iter().all(|&e| pieces.contains(&r.ep[e as usize]))
+            })
+            .map(|&r| Symmetry::new(r))
+            .collect();
+        moves.classes = SymCoordinate::new(count, &symmetries, |placement, s| {
+            moves
+                .coordinate(&symmetries[s].conjugate(&moves.cube_at(placement, 0)))
+                .0
+        });
+        let (solved, _) = moves.coordinate(&CubieCube::SOLVED);
+        moves.class_flips = symmetries
+            .iter()
+            .map(|symmetry| {
+                (0..1 << edges)
+                    .map(|flips| {
+                        let cube = symmetry.conjugate(&moves.cube_at(solved, flips));
+                        moves.coordinate(&cube).1 as u16
+                    })
+                    .collect()
+            })
+            .collect();
+        moves
+    }
+
+    /// The position of the places of the edges among all ways to place them.
+    fn placement(&self, places: &[u8]) -> usize {
+        places.iter().enumerate().fold(0, |index, (k, &p)| {
+            let before = places[..k].iter().filter(|&&q| q < p).count();
+            index * (12 - k) + p as usize - before
+        })
+    }
+
+    /// The places of the edges at a position, the opposite of
+    /// [`EdgeMoves::placement`].
+    fn places_of(&self, mut index: usize) -> Vec<u8> {
+        let mut digits = vec![0; self.edges];
+        for (k, d) in digits.iter_mut().enumerate().rev() {
+            *d = index % (12 - k);
+            index /= 12 - k;
+        }
+        let mut unused: Vec<u8> = (0..12).collect();
+        digits.into_iter().map(|d| unused.remove(d)).collect()
+    }
+
+    /// The placement and flips of the edges of a cube.
+    pub(crate) fn coordinate(&self, cube: &CubieCube) -> (usize, usize) {
+        let places: Vec<u8> = self
+            .pieces
+            .iter()
+            .map(|e| cube.ep.iter().position(|p| p == e).unwrap() as u8)
+            .collect();
+        let flips = places
+            .iter()
+            .enumerate()
+            .fold(0, |f, (k, &p)| f | (cube.eo[p as usize] as usize) << k);
+        (self.placement(&places), flips)
+    }
+
+    /// A cube with the edges at a placement and flips, and the other edges in
+    /// the places left in order.
+    fn cube_at(&self, placement: usize, flips: usize) -> CubieCube {
+        let places = self.places_of(placement);
+        let mut others = (0..12).filter(|e| !self.pieces.contains(e));
+        let mut cube = CubieCube::SOLVED;
+        for place in 0..12 {
+            match places.iter().position(|&p| p as usize == place) {
+                Some(k) => {
+                    cube.ep[place] = self.pieces[k];
+                    cube.eo[place] = (flips >> k & 1) as u8;
+                }
+                None => cube.ep[place] = others.next().expect("every place has an edge"),
+            }
+        }
+        cube
+    }
+
+    /// The index in the edge database of a placement and flips.
+    pub(crate) fn index(&self, placement: usize, flips: usize) -> usize {
+        let (class, flips) = self.classes.reduce(placement, flips, &self.class_flips);
+        class << self.edges | flips
+    }
+
+    /// The fewest turns solving the edges from every class of placements and
+    /// flips, indexed by `class << edges | flips`.
+    pub(crate) fn distances(&self) -> Nibbles {
+        let edges = self.edges;
+        let (placement, flips) = self.coordinate(&CubieCube::SOLVED);
+        Nibbles::distances(
+            self.classes.len() << edges,
+            self.index(placement, flips),
+            |i, m| {
+                let placement = self.classes.representative(i >> edges);
+                let flips = i & ((1 << edges) - 1);
+                self.index(
+                    self.places[placement][m] as usize,
+                    flips ^ self.flips[placement][m] as usize,
+                )
+            },
+        )
+    }
+}
+
+/// Tables of the fewest turns needed to solve parts of a 3x3x3, used by
+/// [`OptimalSolver`] to skip states that can't be solved in time.
+///
+/// There is one table for the corners and one for the six edges `UR`, `UL`,
+/// `UF`, `UB`, `FR` and `BL`. States that a rotation of the whole cube turns
+/// into each other take as many turns to solve, so each table only stores one
+/// state of each symmetry class: the corners by the eight rotations keeping
+/// the up-down axis and the edges by a half turn about it. The edge table is
+/// looked up for the cube seen from several sides, see [`OptimalSolver`]. The
+/// tables take about 16MB and are slow to make, so they can be saved to a
+/// file with [`PatternDatabases::save`] and read back later.
+pub struct PatternDatabases {
+    corners: Option<Nibbles>,
+    edges: Nibbles,
+    edge_moves: EdgeMoves,
+}
+
+/// The start of a file of pattern databases, followed by the format version.
+const MAGIC: &[u8; 4] = b"PCPD";
+const VERSION: u8 = 2;
+
+impl PatternDatabases {
+    /// Make the tables, which takes several seconds with optimizations.
+    pub fn generate() -> Self {
+        Self::build(true, EDGES.len())
+    }
+
+    /// Make tables for the corners, if wanted, and the first `edges` of the
+    /// stored edges.
+    fn build(corners: bool, edges: usize) -> Self {
+        let edge_moves = EdgeMoves::new(&EDGES[..edges]);
+        let edge_table = edge_moves.distances();
+        let corners = corners.then(|| {
+            let moves = move_tables();
+            let symmetries = corner_symmetries();
+            Nibbles::distances(
+                symmetries.orders.len() * TWISTS,
+                symmetries.index(0, 0),
+                |i, m| {
+                    let order = symmetries.orders.representative(i / TWISTS);
+                    symmetries.index(
+                        moves.corners[order][m] as usize,
+                        moves.twist[i % TWISTS][m] as usize,
+                    )
+                },
+            )
+        });
+        PatternDatabases {
+            corners,
+            edges: edge_table,
+            edge_moves,
+        }
+    }
+
+    /// Read tables written by [`PatternDatabases::write_to`].
+    pub fn read_from<R: Read>(mut reader: R) -> Result<Self, Error> {
+        let mut header = [0; 7];
+        reader.read_exact(&mut header).map_err(read_error)?;
+        let (corners, edges) = (header[5], header[6] as usize);
+        if &header[..4] != MAGIC || header[4] != VERSION || corners > 1 || edges > EDGES.len() {
+            return Err(Error::InvalidPatternDatabase);
+        }
+        let mut databases = Self {
+            corners: None,
+            edges: Nibbles::new(0),
+            edge_moves: EdgeMoves::new(&EDGES[..edges]),
+        };
+        if corners == 1 {
+            let mut table = Nibbles::new(corner_symmetries().orders.len() * TWISTS);
+            reader.read_exact(&mut table.bytes).map_err(read_error)?;
+            databases.corners = Some(table);
+        }
+        databases.edges = Nibbles::new(databases.edge_moves.classes.len() << edges);
+        reader
+            .read_exact(&mut databases.edges.bytes)
+            .map_err(read_error)?;
+        if reader.read(&mut [0]).map_err(read_error)? != 0 {
+            return Err(Error::InvalidPatternDatabase);
+        }
+        Ok(databases)
+    }
+
+    /// Write the tables so they can be read with [`PatternDatabases::read_from`].
+    pub fn write_to<W: Write>(&self, mut writer: W) -> Result<(), Error> {
+        let edges = self.edge_moves.edges as u8;
+        let corners = u8::from(self.corners.is_some());
+        writer.write_all(MAGIC).map_err(io_error)?;
+        writer
+            .write_all(&[VERSION, corners, edges])
+            .map_err(io_error)?;
+        if let Some(table) = &self.corners {
+            writer.write_all(&table.bytes).map_err(io_error)?;
+        }
+        writer.write_all(&self.edges.bytes).map_err(io_error)?;
+        writer.flush().map_err(io_error)
+    }
+
+    /// Read tables from a file made by [`PatternDatabases::save`].
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        Self::read_from(BufReader::new(File::open(path).map_err(io_error)?))
+    }
+
+    /// Write the tables to a file.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), Error> {
+        self.write_to(BufWriter::new(File::create(path).map_err(io_error)?))
+    }
+
+    /// Read tables from a file, or make them and save them there if it doesn't
+    /// exist yet.
+    pub fn load_or_generate<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        let path = path.as_ref();
+        if path.exists() {
+            return Self::load(path);
+        }
+        let databases = Self::generate();
+        databases.save(path)?;
+        Ok(databases)
+    }
+
+    /// A lower bound on the turns needed to solve a searched state.
+    fn bound(&self, node: &Node) -> usize {
+        let corners = self.corners.as_ref().map_or(0, |t| {
+            t.get(corner_symmetries().index(node.corners, node.twist))
+        });
+        node.edges
+            .iter()
+            .map(|&(placement, flips)| self.edges.get(self.edge_moves.index(placement, flips)))
+            .fold(corners, u8::max) as usize
+    }
+}
+
+impl fmt::Debug for PatternDatabases {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PatternDatabases")
+            .field("corners", &self.corners.is_some())
+            .field("edges", &self.edge_moves.edges)
+            .finish()
+    }
+}
+
+fn io_error(e: io::Error) -> Error {
+    Error::Io(e.to_string())
+}
+
+fn read_error(e: io::Error) -> Error {
+    match e.kind() {
+        io::ErrorKind::UnexpectedEof => Error::InvalidPatternDatabase,
+        _ => io_error(e),
+    }
+}
+
+/// A state of the search as the coordinates of the pattern databases, the
+/// edges as seen through each of the [`lookups`].
+#[derive(Debug, Clone, Copy)]
+struct Node {
+    corners: usize,
+    twist: usize,
+    edges: [(usize, usize); LOOKUPS],
+}
+
+impl Node {
+    fn new(databases: &PatternDatabases, cube: &CubieCube) -> Self {
+        let mut edges = [(0, 0); LOOKUPS];
+        for (edges, lookup) in edges.iter_mut().zip(lookups()) {
+            *edges = databases.edge_moves.coordinate(&lookup.conjugate(cube));
+        }
+        Node {
+            corners: cube.corners(),
+            twist: cube.twist(),
+            edges,
+        }
+    }
+}
+
+/// Finds the shortest solutions of a 3x3x3 with Korf's algorithm.
+///
+/// Solutions of each length are searched in turn, using [`PatternDatabases`]
+/// to skip states that can't be solved in the turns left. The search is split
+/// by the first turn between several threads. The edge table is looked up for
+/// the cube seen from six sides, each pair of which sees every edge once, and
+/// the most turns any lookup needs is used. Most cubes
+/// need 17 or 18 turns, which can take minutes to search.
+#[derive(Debug)]
+pub struct OptimalSolver {
+    databases: PatternDatabases,
+    threads: usize,
+}
+
+impl OptimalSolver {
+    /// Create a solver searching on as many threads as there are processors.
+    pub fn new(databases: PatternDatabases) -> Self {
+        let threads = thread::available_parallelism().map_or(1, |n| n.get());
+        OptimalSolver { databases, threads }
+    }
+
+    /// Search on a different number of threads, at least one.
+    pub fn with_threads(mut self, threads: usize) -> Self {
+        self.threads = threads.max(1);
+        self
+    }
+
+    /// The tables used to skip states.
+    pub fn databases(&self) -> &PatternDatabases {
+        &self.databases
+    }
+
+    /// One of the shortest sequences of face turns solving a 3x3x3 as it is
+    /// held, see [`CubieCube::from_cube`].
+    pub fn solve(&self, cube: &Cube) -> Result<Vec<Move>, Error> {
+        self.solve_cubies(&CubieCube::from_cube(cube)?)
+    }
+
+    /// One of the shortest sequences of face turns solving a cubie cube.
+    ///
+    /// Returns [`Error::Unsolvable`] if the cube can't be solved by turning the
+    /// sides.
+    pub fn solve_cubies(&self, cube: &CubieCube) -> Result<Vec<Move>, Error> {
+        if !cube.is_solvable() {
+            return Err(Error::Unsolvable);
+        }
+        let root = Node::new(&self.databases, cube);
+        let search = Search {
+            databases: &self.databases,
+            cube: *cube,
+            stop: AtomicBool::new(false),
+            solution: Mutex::new(None),
+        };
+        for depth in self.databases.bound(&root).. {
+            if depth == 0 {
+                if cube.is_solved() {
+                    return Ok(vec![]);
+                }
+                continue;
+            }
+            let next = AtomicUsize::new(0);
+            thread::scope(|scope| {
+                for _ in 0..self.threads {
+                    scope.spawn(|| loop {
+                        let m = next.fetch_add(1, Ordering::Relaxed);
+                        if m >= FACE_TURNS || search.stop.load(Ordering::Relaxed) {
+                            break;
+                        }
+                        if let Some(child) = search.turn(&root, m, depth - 1) {
+                            search.search(child, depth - 1, &mut vec![m]);
+                        }
+                    });
+                }
+            });
+            if let Some(turns) = search.solution.lock().unwrap().take() {
+                return Ok(turns.into_iter().map(face_turn).collect());
+            }
+        }
+        unreachable!("every solvable cube has a solution")
+    }
+}
+
+/// What the threads searching for a solution share.
+struct Search<'a> {
+    databases: &'a PatternDatabases,
+    cube: CubieCube,
+    stop: AtomicBool,
+    solution: Mutex<Option<Vec<usize>>>,
+}
+
+impl Search<'_> {
+    /// The state reached by face turn `m`, unless the tables show it can't
+    /// be solved in `togo` turns. Each table is only looked up if the ones
+    /// before it didn't show that.
+    fn turn(&self, node: &Node, m: usize, togo: usize) -> Option<Node> {
+        let moves = move_tables();
+        let togo = togo as u8;
+        let (corners, twist) = (
+            moves.corners[node.corners][m] as usize,
+            moves.twist[node.twist][m] as usize,
+        );
+        if let Some(table) = &self.databases.corners {
+            if table.get(corner_symmetries().index(corners, twist)) > togo {
+                return None;
+            }
+        }
+        let edge_moves = &self.databases.edge_moves;
+        let mut edges = node.edges;
+        for ((placement, flips), lookup) in edges.iter_mut().zip(lookups()) {
+            let m = lookup.turns[m];
+            *flips ^= edge_moves.flips[*placement][m] as usize;
+            *placement = edge_moves.places[*placement][m] as usize;
+            if self
+                .databases
+                .edges
+                .get(edge_moves.index(*placement, *flips))
+                > togo
+            {
+                return None;
+            }
+        }
+        Some(Node {
+            corners,
+            twist,
+            edges,
+        })
+    }
+
+    /// Search for solutions of exactly `togo` more turns after `turns`,
+    /// returning whether one was found.
+    fn search(&self, node: Node, togo: usize, turns: &mut Vec<usize>) -> bool {
+        if self.stop.load(Ordering::Relaxed) {
+            return false;
+        }
+        if togo == 0 {
+            // The tables only see part of the cube when some are left out.
+            let solved = turns.iter().fold(self.cube, |c, &m| c.turn(m)).is_solved();
+            if solved {
+                self.stop.store(true, Ordering::Relaxed);
+                *self.solution.lock().unwrap() = Some(turns.clone());
+            }
+            return solved;
+        }
+        for m in 0..FACE_TURNS {
+            if is_redundant(turns.last().copied(), m) {
+                continue;
+            }
+            let child = match self.turn(&node, m, togo - 1) {
+                Some(child) => child,
+                None => continue,
+            };
+            turns.push(m);
+            let found = self.search(child, togo - 1, turns);
+            turns.pop();
+            if found {
+                return true;
+            }
+        }
+        false
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::algorithm::Algorithm;
+
+    /// Tables of the four up layer edges only, quick enough to make in every
+    /// test run.
+    fn small() -> &'static PatternDatabases {
+        static SMALL: OnceLock<PatternDatabases> = OnceLock::new();
+        SMALL.get_or_init(|| PatternDatabases::build(false, 4))
+    }
+
+    #[test]
+    fn test_symmetries() {
+        for lookup in lookups() {
+            for m in 0..FACE_TURNS {
+                let seen = lookup.conjugate(&CubieCube::SOLVED.turn(m));
+                assert_eq!(seen, CubieCube::SOLVED.turn(lookup.turns[m]));
+            }
+        }
+        // Each pair of lookups sees every edge once.
+        for edge in 0..12 {
+            let seen = lookups()
+                .iter()
+                .filter(|lookup| EDGES.contains(&lookup.inverse.ep[edge]))
+                .count();
+            assert_eq!(seen, 3, "{}", edge);
+        }
+
+        let symmetries = corner_symmetries();
+        assert!(symmetries.orders.len() > CORNERS / 8);
+        assert!(symmetries.orders.len() < CORNERS / 7);
+        // Cubes seen through any of the symmetries share an index.
+        let mut cube = CubieCube::SOLVED;
+        for &m in [2, 7, 13, 16, 0, 10, 4, 5, 9].iter() {
+            cube = cube.turn(m);
+            let index = symmetries.index(cube.corners(), cube.twist());
+            for symmetry in ud_rotations().iter() {
+                let seen = symmetry.conjugate(&cube);
+                assert_eq!(symmetries.index(seen.corners(), seen.twist()), index);
+            }
+        }
+    }
+
+    #[test]
+    fn test_edge_moves() {
+        let moves = &small().edge_moves;
+        assert_eq!(moves.places.len(), 12 * 11 * 10 * 9);
+        // A half turn about the up axis keeps the edges, so most classes have
+        // two placements.
+        assert!(moves.classes.len() > moves.places.len() / 2);
+        assert!(moves.classes.len() < moves.places.len() * 2 / 3);
+        for i in [0, 17, 4000, 11879].iter() {
+            assert_eq!(moves.placement(&moves.places_of(*i)), *i);
+            assert_eq!(moves.coordinate(&moves.cube_at(*i, 5)), (*i, 5));
+        }
+        let mut cube = CubieCube::SOLVED;
+        let (mut edges, mut flips) = moves.coordinate(&cube);
+        assert_eq!((edges, flips), (moves.placement(&[0, 2, 1, 3]), 0));
+        for &m in [2, 7, 13, 16, 0, 10].iter() {
+            flips ^= moves.flips[edges][m] as usize;
+            edges = moves.places[edges][m] as usize;
+            cube = cube.turn(m);
+            assert_eq!((edges, flips), moves.coordinate(&cube));
+        }
+    }
+
+    #[test]
+    fn test_distances() {
+        // The table stored by symmetry has the same distances as one with
+        // every placement.
+        let moves = &small().edge_moves;
+        let edges = moves.edges;
+        let (placement, flips) = moves.coordinate(&CubieCube::SOLVED);
+        let full = Nibbles::distances(
+            moves.places.len() << edges,
+            placement << edges | flips,
+            |i, m| {
+                let (placement, flips) = (i >> edges, i & ((1 << edges) - 1));
+                (moves.places[placement][m] as usize) << edges
+                    | (flips ^ moves.flips[placement][m] as usize)
+            },
+        );
+        for i in 0..full.len {
+            let (placement, flips) = (i >> edges, i & ((1 << edges) - 1));
+            assert_eq!(
+                small().edges.get(moves.index(placement, flips)),
+                full.get(i)
+            );
+        }
+    }
+
+    #[test]
+    fn test_solve() {
+        let solver = OptimalSolver::new(PatternDatabases::build(false, 4)).with_threads(3);
+        assert_eq!(solver.solve(&Cube::new3x3x3()), Ok(vec![]));
+        for (scramble, length) in [("R", 1), ("R U R' U'", 4), ("F2 D' B L", 4)].iter() {
+            let mut cube = Cube::new3x3x3();
+            cube.apply(&scramble.parse().unwrap()).unwrap();
+            let solution = Algorithm::from(solver.solve(&cube).unwrap());
+            assert_eq!(solution.len(), *length, "{}", scramble);
+            cube.apply(&solution).unwrap();
+            assert!(cube.is_solved());
+        }
+        // Two turns of the same side are one turn.
+        let mut cube = Cube::new3x3x3();
+        cube.apply(&"U R R U'".parse().unwrap()).unwrap();
+        assert_eq!(solver.solve(&cube).unwrap().len(), 3);
+
+        let mut twisted = CubieCube::SOLVED;
+        twisted.eo[0] = 1;
+        assert_eq!(solver.solve_cubies(&twisted), Err(Error::Unsolvable));
+    }
+
+    #[test]
+    fn test_bound() {
+        let databases = small();
+        let bound = |alg: &str| {
+            let mut cube = CubieCube::SOLVED;
+            cube.apply(&alg.parse().unwrap()).unwrap();
+            databases.bound(&Node::new(databases, &cube))
+        };
+        assert_eq!(bound(""), 0);
+        // Only the down layer edges move, which are seen through `x2`.
+        assert_eq!(bound("D"), 1);
+        assert_eq!(bound("U2 R2"), 2);
+        assert!(bound("R U F' L2 D B'") <= 6);
+    }
+
+    #[test]
+    fn test_read_write() {
+        let databases = small();
+        let mut bytes = Vec::new();
+        databases.write_to(&mut bytes).unwrap();
+        assert_eq!(
+            bytes.len(),
+            7 + (databases.edge_moves.classes.len() << 4) / 2
+        );
+        let read = PatternDatabases::read_from(&bytes[..]).unwrap();
+        assert!(read.edges == databases.edges && read.corners.is_none());
+
+        assert_eq!(
+            PatternDatabases::read_from(&bytes[..bytes.len() - 1]).unwrap_err(),
+            Error::InvalidPatternDatabase
+        );
+        bytes.push(0);
+        assert_eq!(
+            PatternDatabases::read_from(&bytes[..]).unwrap_err(),
+            Error::InvalidPatternDatabase
+        );
+        bytes[0] = b'X';
+        assert_eq!(
+            PatternDatabases::read_from(&bytes[..]).unwrap_err(),
+            Error::InvalidPatternDatabase
+        );
+
+        let path = std::env::temp_dir().join("puzzle-cube-test-small.pcpd");
+        databases.save(&path).unwrap();
+        assert!(PatternDatabases::load(&path).unwrap().edges == databases.edges);
+        std::fs::remove_file(&path).unwrap();
+        assert!(matches!(PatternDatabases::load(&path), Err(Error::Io(_))));
+    }
+
+    #[test]
+    #[ignore = "makes the full tables, which is slow without optimizations"]
+    fn test_generate() {
+        let databases = PatternDatabases::generate();
+        // States seen the same through some symmetries are only stored once,
+        // and the other places for them are never filled.
+        let deepest = |table: &Nibbles| {
+            (0..table.len)
+                .map(|i| table.get(i))
+                .filter(|&d| d != UNKNOWN)
+                .max()
+        };
+        assert_eq!(deepest(databases.corners.as_ref().unwrap()), Some(11));
+        assert_eq!(deepest(&databases.edges), Some(10));
+
+        let solver = OptimalSolver::new(databases);
+        let mut cube = Cube::new3x3x3();
+        let scramble: Algorithm = "R U2 F' L D2 B R' U F2 D' L2 B'".parse().unwrap();
+        cube.apply(&scramble).unwrap();
+        let solution = Algorithm::from(solver.solve(&cube).unwrap());
+        assert!(solution.len() <= scramble.len());
+        cube.apply(&solution).unwrap();
+        assert!(cube.is_solved());
+    }
+}
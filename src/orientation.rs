@@ -0,0 +1,247 @@
+use std::{convert::TryFrom, ops::Mul};
+
+use nalgebra::Matrix3;
+
+use crate::{
+    algorithm::Algorithm,
+    error::Error,
+    facelet::Face,
+    movement::{Axis, Move, MoveType},
+};
+
+/// One of the 24 ways to hold a cube, named after the sides of a cube held the
+/// standard way that are now on top and at the front.
+///
+/// So [`Orientation::UpFront`] is the standard way, and [`Orientation::FrontDown`]
+/// is the cube after an `x` rotation, with the front side on top and the down
+/// side at the front.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Orientation {
+    UpRight,
+    UpFront,
+    UpLeft,
+    UpBack,
+    RightUp,
+    RightFront,
+    RightDown,
+    RightBack,
+    FrontUp,
+    FrontRight,
+    FrontDown,
+    FrontLeft,
+    DownRight,
+    DownFront,
+    DownLeft,
+    DownBack,
+    LeftUp,
+    LeftFront,
+    LeftDown,
+    LeftBack,
+    BackUp,
+    BackRight,
+    BackDown,
+    BackLeft,
+}
+
+impl Orientation {
+    /// Every orientation, by the side on top in the order of [`Face::ALL`] and
+    /// then by the side at the front.
+    pub const ALL: [Orientation; 24] = [
+        Orientation::UpRight,
+        Orientation::UpFront,
+        Orientation::UpLeft,
+        Orientation::UpBack,
+        Orientation::RightUp,
+        Orientation::RightFront,
+        Orientation::RightDown,
+        Orientation::RightBack,
+        Orientation::FrontUp,
+        Orientation::FrontRight,
+        Orientation::FrontDown,
+        Orientation::FrontLeft,
+        Orientation::DownRight,
+        Orientation::DownFront,
+        Orientation::DownLeft,
+        Orientation::DownBack,
+        Orientation::LeftUp,
+        Orientation::LeftFront,
+        Orientation::LeftDown,
+        Orientation::LeftBack,
+        Orientation::BackUp,
+        Orientation::BackRight,
+        Orientation::BackDown,
+        Orientation::BackLeft,
+    ];
+
+    /// The orientation with one side on top and another at the front, if they
+    /// are next to each other.
+    pub fn new(top: Face, front: Face) -> Option<Orientation> {
+        Orientation::ALL
+            .iter()
+            .copied()
+            .find(|o| o.top() == top && o.front() == front)
+    }
+
+    /// The side on top, where it is when the cube is held the standard way.
+    pub fn top(self) -> Face {
+        Face::ALL[self as usize / 4]
+    }
+
+    /// The side at the front, where it is when the cube is held the standard way.
+    pub fn front(self) -> Face {
+        let top = self.top();
+        Face::ALL
+            .iter()
+            .copied()
+            .filter(|&f| f != top && f.normal() != -top.normal())
+            .nth(self as usize % 4)
+            .expect("every side has four sides next to it")
+    }
+
+    /// The orientation reached by turning this one and then `other`, both
+    /// starting from the standard way.
+    pub fn multiply(self, other: Orientation) -> Orientation {
+        let product = Matrix3::from(other) * Matrix3::from(self);
+        Orientation::try_from(product).expect("rotations are closed under multiplication")
+    }
+
+    /// The orientation that turns this one back to the standard way.
+    pub fn inverse(self) -> Orientation {
+        Orientation::try_from(Matrix3::from(self).transpose())
+            .expect("the inverse of a rotation is its transpose")
+    }
+
+    /// The fewest `x`, `y` and `z` rotations that turn a cube held the standard
+    /// way to this orientation, never more than two.
+    pub fn to_algorithm(self) -> Algorithm {
+        let mut rotations = Vec::with_capacity(9);
+        for &axis in [Axis::Y, Axis::Z, Axis::X].iter() {
+            for &move_type in [
+                MoveType::Clockwise,
+                MoveType::CounterClockwise,
+                MoveType::Twice,
+            ]
+            .iter()
+            {
+                let mv = Move::rotate_cube(axis, move_type);
+                rotations.push((mv, Orientation::from_move(&mv)));
+            }
+        }
+        if self == Orientation::default() {
+            return Algorithm::new();
+        }
+        if let Some((mv, _)) = rotations.iter().find(|(_, o)| *o == self) {
+            return vec![*mv].into();
+        }
+        rotations
+            .iter()
+            .flat_map(|first| rotations.iter().map(move |second| (first, second)))
+            .find(|((_, a), (_, b))| a.multiply(*b) == self)
+            .map(|((a, _), (b, _))| vec![*a, *b].into())
+            .expect("every orientation is at most two rotations away")
+    }
+
+    /// The orientation a whole cube rotation turns to from the standard way.
+    fn from_move(mv: &Move) -> Orientation {
+        Orientation::try_from(*mv.rotation_matrix()).expect("moves turn by rotations")
+    }
+}
+
+impl Default for Orientation {
+    /// The standard way, with the up side on top and the front side at the front.
+    fn default() -> Self {
+        Orientation::UpFront
+    }
+}
+
+impl Mul for Orientation {
+    type Output = Orientation;
+
+    /// Turn to `self` and then `rhs`, see [`Orientation::multiply`].
+    fn mul(self, rhs: Orientation) -> Self::Output {
+        self.multiply(rhs)
+    }
+}
+
+impl From<Orientation> for Matrix3<isize> {
+    /// The rotation turning every piece of a cube held the standard way to
+    /// hold it in the orientation.
+    fn from(o: Orientation) -> Self {
+        // The rows are the sides that end up at the front, right and top.
+        let top = o.top().normal();
+        let front = o.front().normal();
+        Matrix3::from_rows(&[
+            front.transpose(),
+            top.cross(&front).transpose(),
+            top.transpose(),
+        ])
+    }
+}
+
+impl TryFrom<Matrix3<isize>> for Orientation {
+    type Error = Error;
+
+    /// The orientation a rotation turns a cube held the standard way to.
+    fn try_from(m: Matrix3<isize>) -> Result<Self, Self::Error> {
+        Orientation::ALL
+            .iter()
+            .copied()
+            .find(|&o| Matrix3::from(o) == m)
+            .ok_or(Error::InvalidRotation)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_faces() {
+        for &o in Orientation::ALL.iter() {
+            assert_eq!(Orientation::new(o.top(), o.front()), Some(o));
+            let m = Matrix3::from(o);
+            assert_eq!(Orientation::try_from(m), Ok(o));
+            assert_eq!(crate::cubit::determinant(&m), 1);
+        }
+        assert_eq!(Orientation::new(Face::Up, Face::Down), None);
+        assert_eq!(Orientation::new(Face::Left, Face::Left), None);
+        assert_eq!(
+            Orientation::try_from(-Matrix3::identity()),
+            Err(Error::InvalidRotation)
+        );
+        assert_eq!(Matrix3::from(Orientation::default()), Matrix3::identity());
+    }
+
+    #[test]
+    fn test_multiply() {
+        for &a in Orientation::ALL.iter() {
+            assert_eq!(a * a.inverse(), Orientation::default());
+            assert_eq!(a.inverse() * a, Orientation::default());
+            for &b in Orientation::ALL.iter() {
+                assert_eq!(Matrix3::from(a * b), Matrix3::from(b) * Matrix3::from(a));
+            }
+        }
+        let x = Orientation::FrontDown;
+        let y = Orientation::UpRight;
+        assert_eq!(x * y, Orientation::FrontRight);
+        assert_eq!(y * x, Orientation::RightDown);
+    }
+
+    #[test]
+    fn test_to_algorithm() {
+        assert!(Orientation::default().to_algorithm().is_empty());
+        assert_eq!(Orientation::FrontDown.to_algorithm().to_string(), "x");
+        assert_eq!(Orientation::UpRight.to_algorithm().to_string(), "y");
+        assert_eq!(Orientation::LeftFront.to_algorithm().to_string(), "z");
+        assert_eq!(Orientation::DownBack.to_algorithm().to_string(), "x2");
+        for &o in Orientation::ALL.iter() {
+            let alg = o.to_algorithm();
+            assert!(alg.len() <= 2);
+            let reached = alg
+                .iter()
+                .map(Orientation::from_move)
+                .fold(Orientation::default(), Orientation::multiply);
+            assert_eq!(reached, o);
+        }
+    }
+}
@@ -0,0 +1,295 @@
+use nalgebra::Vector3;
+
+use crate::{facelet::Face, orientation::Orientation};
+
+/// A place on a cube, by the layer it is in along each axis.
+///
+/// Layers are counted from `0` to one less than the number of sides, so on a
+/// 3x3x3 `Position::new(2, 2, 2)` is the corner at UFR and `Position::new(0, 1, 1)`
+/// is the center of the back side.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Position {
+    /// The layer from the back to the front side.
+    pub x: usize,
+    /// The layer from the left to the right side.
+    pub y: usize,
+    /// The layer from the down to the up side.
+    pub z: usize,
+}
+
+impl Position {
+    /// The position in layer `x` from the back, `y` from the left and `z` from
+    /// the down side.
+    pub fn new(x: usize, y: usize, z: usize) -> Self {
+        Position { x, y, z }
+    }
+
+    /// How many of the outside layers the position is in, `0` if it is inside
+    /// the cube or not on it at all.
+    fn outside_layers(self, sides: usize) -> usize {
+        let coords = [self.x, self.y, self.z];
+        if coords.iter().any(|&v| v >= sides) {
+            return 0;
+        }
+        coords.iter().filter(|&&v| v == 0 || v == sides - 1).count()
+    }
+}
+
+/// A piece of a cube, named by the position it is at when the cube is solved.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct PieceId(Position);
+
+impl PieceId {
+    /// The piece whose home is at a position.
+    pub fn new(home: Position) -> Self {
+        PieceId(home)
+    }
+
+    /// The position the piece is at when the cube is solved.
+    pub fn home(self) -> Position {
+        self.0
+    }
+}
+
+/// What a piece is by how many stickers it has, see [`PieceKind::piece_type`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum PieceType {
+    Corner,
+    Edge,
+    Center,
+}
+
+impl PieceType {
+    /// The type of the piece at a position on a cube with a number of sides,
+    /// if there is one.
+    pub(crate) fn at(position: Position, sides: usize) -> Option<PieceType> {
+        match position.outside_layers(sides) {
+            3 => Some(PieceType::Corner),
+            2 => Some(PieceType::Edge),
+            1 => Some(PieceType::Center),
+            _ => None,
+        }
+    }
+}
+
+/// What a piece is, and for big cubes which orbit it is in.
+///
+/// Moves only ever swap pieces of the same kind with each other, so every
+/// kind is an orbit. Orbits of edges and centers are numbered by the layer
+/// they are in counted from the outside, starting at `1`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum PieceKind {
+    Corner,
+    /// The edge in the middle of an edge of an odd cube.
+    Midge,
+    /// An edge in a layer counted from the corners.
+    Wing(usize),
+    /// The center in the middle of a side of an odd cube.
+    Center,
+    /// A center on a diagonal of a side, in a layer counted from its corners.
+    XCenter(usize),
+    /// A center on a line through the middle of a side of an odd cube, in a
+    /// layer counted from its edges.
+    PlusCenter(usize),
+    /// Any other center, by the layers it is in counted from the right and the
+    /// down edge when its side is turned so the piece is in the down right
+    /// quarter. Mirror images are in different orbits.
+    Oblique(usize, usize),
+}
+
+impl PieceKind {
+    /// The kind of the piece whose home is at a position on a cube with a
+    /// number of sides, if there is one.
+    pub fn at(home: Position, sides: usize) -> Option<PieceKind> {
+        let outside = home.outside_layers(sides);
+        if outside == 0 {
+            return None;
+        }
+        // Twice the coordinates from the middle of the cube, so they are whole
+        // numbers for even cubes.
+        let n = sides as isize;
+        let pos = Vector3::new(home.x, home.y, home.z).map(|v| 2 * v as isize - (n - 1));
+        let layer = |v: isize| ((n - 1 - v.abs()) / 2) as usize;
+        Some(match outside {
+            3 => PieceKind::Corner,
+            2 => {
+                let v = *pos
+                    .iter()
+                    .find(|v| v.abs() != n - 1)
+                    .expect("an edge is inside one layer");
+                if v == 0 {
+                    PieceKind::Midge
+                } else {
+                    PieceKind::Wing(layer(v))
+                }
+            }
+            _ => {
+                let normal = pos.map(|v| v / (n - 1));
+                let face = Face::from_normal(&normal).expect("a center is on one side");
+                let (mut u, mut v) = (face.right().dot(&pos), face.down().dot(&pos));
+                match (u, v) {
+                    (0, 0) => PieceKind::Center,
+                    (0, v) | (v, 0) => PieceKind::PlusCenter(layer(v)),
+                    _ if u.abs() == v.abs() => PieceKind::XCenter(layer(u)),
+                    _ => {
+                        // Quarter turns of the side keep the orbit.
+                        while u < 0 || v < 0 {
+                            (u, v) = (v, -u);
+                        }
+                        PieceKind::Oblique(layer(u), layer(v))
+                    }
+                }
+            }
+        })
+    }
+
+    /// Whether the piece is a corner, an edge or a center.
+    pub fn piece_type(self) -> PieceType {
+        match self {
+            PieceKind::Corner => PieceType::Corner,
+            PieceKind::Midge | PieceKind::Wing(_) => PieceType::Edge,
+            PieceKind::Center
+            | PieceKind::XCenter(_)
+            | PieceKind::PlusCenter(_)
+            | PieceKind::Oblique(..) => PieceType::Center,
+        }
+    }
+}
+
+/// A piece of a cube and where it is, see [`Cube::pieces`](crate::Cube::pieces).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Piece {
+    pub(crate) id: PieceId,
+    pub(crate) position: Position,
+    pub(crate) orientation: Orientation,
+    pub(crate) kind: PieceKind,
+}
+
+impl Piece {
+    /// Which piece it is, named by its home.
+    pub fn id(&self) -> PieceId {
+        self.id
+    }
+
+    /// The position the piece is at now.
+    pub fn position(&self) -> Position {
+        self.position
+    }
+
+    /// How the piece is turned from the way it is on a solved cube, as if it
+    /// were a whole cube held in the orientation.
+    pub fn orientation(&self) -> Orientation {
+        self.orientation
+    }
+
+    /// What the piece is, and which orbit it is in.
+    pub fn kind(&self) -> PieceKind {
+        self.kind
+    }
+
+    /// Whether the piece is a corner, an edge or a center.
+    pub fn piece_type(&self) -> PieceType {
+        self.kind.piece_type()
+    }
+}
+
+/// How far the pieces of an orbit are from solved, see
+/// [`Cube::orbits`](crate::Cube::orbits).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Orbit {
+    pub(crate) kind: PieceKind,
+    pub(crate) pieces: usize,
+    pub(crate) solved: usize,
+    pub(crate) odd: Option<bool>,
+}
+
+impl Orbit {
+    /// Which orbit it is.
+    pub fn kind(&self) -> PieceKind {
+        self.kind
+    }
+
+    /// The number of pieces in the orbit.
+    pub fn pieces(&self) -> usize {
+        self.pieces
+    }
+
+    /// The number of pieces showing the stickers of a solved cube.
+    pub fn solved(&self) -> usize {
+        self.solved
+    }
+
+    /// Whether every piece of the orbit is solved.
+    pub fn is_solved(&self) -> bool {
+        self.solved == self.pieces
+    }
+
+    /// Whether it takes an odd number of swaps to put the pieces where they
+    /// belong, `None` for centers that have pieces looking the same.
+    pub fn is_odd(&self) -> Option<bool> {
+        self.odd
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{cube::Cube, scramble::MoveScrambler};
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_kind() {
+        let at = |x, y, z, sides| PieceKind::at(Position::new(x, y, z), sides);
+        assert_eq!(at(0, 0, 0, 2), Some(PieceKind::Corner));
+        assert_eq!(at(2, 0, 2, 3), Some(PieceKind::Corner));
+        assert_eq!(at(1, 0, 2, 3), Some(PieceKind::Midge));
+        assert_eq!(at(1, 1, 2, 3), Some(PieceKind::Center));
+        assert_eq!(at(1, 1, 1, 3), None);
+        assert_eq!(at(3, 0, 0, 3), None);
+        assert_eq!(at(0, 2, 3, 4), Some(PieceKind::Wing(1)));
+        assert_eq!(at(2, 1, 3, 4), Some(PieceKind::XCenter(1)));
+        assert_eq!(at(4, 0, 3, 5), Some(PieceKind::Wing(1)));
+        assert_eq!(at(2, 4, 1, 5), Some(PieceKind::PlusCenter(1)));
+        assert_eq!(at(6, 2, 4, 7), Some(PieceKind::XCenter(2)));
+        assert_eq!(at(6, 2, 5, 7), Some(PieceKind::Oblique(2, 1)));
+        assert_eq!(at(6, 4, 5, 7), Some(PieceKind::Oblique(1, 2)));
+        assert_eq!(PieceKind::Wing(2).piece_type(), PieceType::Edge);
+        assert_eq!(PieceKind::Oblique(1, 2).piece_type(), PieceType::Center);
+    }
+
+    #[test]
+    fn test_orbits() {
+        for sides in 2..=8 {
+            let mut cube = Cube::with_number_sides(sides).unwrap();
+            let mut kinds = HashMap::new();
+            for piece in cube.pieces() {
+                assert_eq!(
+                    PieceType::at(piece.position(), sides),
+                    Some(piece.piece_type())
+                );
+                *kinds.entry(piece.kind()).or_insert(0) += 1;
+            }
+            for (&kind, &count) in kinds.iter() {
+                let expected = match kind {
+                    PieceKind::Corner => 8,
+                    PieceKind::Midge => 12,
+                    PieceKind::Center => 6,
+                    _ => 24,
+                };
+                assert_eq!(count, expected, "{:?} on {} sides", kind, sides);
+            }
+            // Moves keep every piece in its orbit.
+            let mut scrambler = MoveScrambler::from_seed(sides, sides as u64).unwrap();
+            cube.apply(&scrambler.scramble()).unwrap();
+            for piece in cube.pieces() {
+                assert_eq!(
+                    PieceKind::at(piece.position(), sides),
+                    Some(piece.kind()),
+                    "{:?} on {} sides",
+                    piece,
+                    sides
+                );
+            }
+        }
+    }
+}
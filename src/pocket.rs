@@ -0,0 +1,281 @@
+use crate::{
+    coord::{move_table, MoveTable},
+    cube::Cube,
+    cubie::{face_turn, perm_index, set_perm, CubieCube, DBL},
+    error::Error,
+    movement::Move,
+};
+use std::sync::OnceLock;
+
+/// The face turns of the up, right and front sides, which leave the down back
+/// left corner of a 2x2x2 in its place.
+const TURNS: [usize; 9] = [0, 1, 2, 3, 4, 5, 6, 7, 8];
+
+/// The quarter turns of the up, right and front sides.
+const QUARTER_TURNS: [usize; 6] = [0, 2, 3, 5, 6, 8];
+
+/// Orders of the seven corners other than the down back left one.
+const CORNERS: usize = 5040;
+
+/// Twists of the six corners whose twist doesn't follow from the others.
+const TWISTS: usize = 729;
+
+/// How turns are counted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Metric {
+    /// Every turn of a side counts as one, including half turns.
+    #[default]
+    HalfTurn,
+    /// Every quarter turn of a side counts as one, so half turns count as two.
+    QuarterTurn,
+}
+
+impl Metric {
+    fn turns(self) -> &'static [usize] {
+        match self {
+            Metric::HalfTurn => &TURNS,
+            Metric::QuarterTurn => &QUARTER_TURNS,
+        }
+    }
+}
+
+/// Finds the shortest solutions of a 2x2x2.
+///
+/// The down back left corner is kept in place so the cube never needs turning
+/// as a whole, leaving 3,674,160 states which can all be solved in 11 turns, or
+/// 14 quarter turns. The number of turns needed for every state is worked out
+/// the first time it is needed for each metric, so solutions are found by
+/// always making a turn that gets one closer to solved.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct PocketSolver {
+    metric: Metric,
+}
+
+impl PocketSolver {
+    /// Create a solver counting half turns as one turn.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Count turns with a different metric.
+    pub fn with_metric(mut self, metric: Metric) -> Self {
+        self.metric = metric;
+        self
+    }
+
+    /// The fewest up, right and front turns solving a 2x2x2 as it is held.
+    ///
+    /// Quarter turns are used when counting quarter turns, so `R R` instead of
+    /// `R2`. Returns [`Error::Unsolvable`] if the corners are twisted in a way
+    /// that can't be solved by turning the sides.
+    pub fn solve(&self, cube: &Cube) -> Result<Vec<Move>, Error> {
+        let state = Self::read(cube)?;
+        Ok(solve_cubies(&state, self.metric)
+            .into_iter()
+            .map(face_turn)
+            .collect())
+    }
+
+    /// The fewest turns needed to solve a 2x2x2, see [`PocketSolver::solve`].
+    pub fn distance(&self, cube: &Cube) -> Result<usize, Error> {
+        let state = Self::read(cube)?;
+        Ok(distances(self.metric)[index(&state)] as usize)
+    }
+
+    fn read(cube: &Cube) -> Result<CubieCube, Error> {
+        let state = CubieCube::from_corners(cube)?;
+        if state.co.iter().sum::<u8>() % 3 != 0 {
+            return Err(Error::Unsolvable);
+        }
+        Ok(state)
+    }
+}
+
+/// The fewest face turns of the metric solving the corners of a cube with the
+/// down back left corner in its place.
+pub(crate) fn solve_cubies(cube: &CubieCube, metric: Metric) -> Vec<usize> {
+    let distances = distances(metric);
+    let moves = move_tables();
+    let (mut corners, mut twist) = (corners(cube), twist(cube));
+    let mut turns = Vec::new();
+    while distances[corners * TWISTS + twist] > 0 {
+        let togo = distances[corners * TWISTS + twist];
+        let m = *metric
+            .turns()
+            .iter()
+            .find(|&&m| {
+                let (c, t) = (moves.corners[corners][m], moves.twist[twist][m]);
+                distances[c as usize * TWISTS + t as usize] < togo
+            })
+            .expect("some turn gets closer to solved");
+        corners = moves.corners[corners][m] as usize;
+        twist = moves.twist[twist][m] as usize;
+        turns.push(m);
+    }
+    turns
+}
+
+/// The order of the corners other than the down back left one.
+fn corners(cube: &CubieCube) -> usize {
+    let mut others = [0; 7];
+    let places = (0..8).filter(|&p| p != DBL);
+    for (c, p) in others.iter_mut().zip(places) {
+        *c = cube.cp[p] - u8::from(cube.cp[p] as usize > DBL);
+    }
+    perm_index(&others)
+}
+
+fn set_corners(cube: &mut CubieCube, corners: usize) {
+    let mut others = [0; 7];
+    set_perm(&mut others, corners);
+    let places = (0..8).filter(|&p| p != DBL);
+    for (c, p) in others.iter().zip(places) {
+        cube.cp[p] = c + u8::from(*c as usize >= DBL);
+    }
+    cube.cp[DBL] = DBL as u8;
+}
+
+/// The twists of the first six corners, the down back left one is never
+/// twisted and the last one follows from the others.
+fn twist(cube: &CubieCube) -> usize {
+    cube.co[..6].iter().fold(0, |t, &o| 3 * t + o as usize)
+}
+
+fn set_twist(cube: &mut CubieCube, twist: usize) {
+    cube.set_twist(3 * twist);
+}
+
+fn index(cube: &CubieCube) -> usize {
+    corners(cube) * TWISTS + twist(cube)
+}
+
+struct MoveTables {
+    corners: MoveTable,
+    twist: MoveTable,
+}
+
+fn move_tables() -> &'static MoveTables {
+    static TABLES: OnceLock<MoveTables> = OnceLock::new();
+    TABLES.get_or_init(|| MoveTables {
+        corners: move_table(CORNERS, &TURNS, set_corners, corners),
+        twist: move_table(TWISTS, &TURNS, set_twist, twist),
+    })
+}
+
+/// The fewest turns of the metric needed to solve every state.
+fn distances(metric: Metric) -> &'static [u8] {
+    static HALF_TURN: OnceLock<Vec<u8>> = OnceLock::new();
+    static QUARTER_TURN: OnceLock<Vec<u8>> = OnceLock::new();
+    let table = match metric {
+        Metric::HalfTurn => &HALF_TURN,
+        Metric::QuarterTurn => &QUARTER_TURN,
+    };
+    table.get_or_init(|| {
+        let moves = move_tables();
+        let mut depths = vec![u8::MAX; CORNERS * TWISTS];
+        depths[0] = 0;
+        let mut frontier = vec![0];
+        let mut depth = 0;
+        while !frontier.is_empty() {
+            depth += 1;
+            let mut next = Vec::new();
+            for index in frontier {
+                let (c, t) = (index / TWISTS, index % TWISTS);
+                for &m in metric.turns() {
+                    let to = moves.corners[c][m] as usize * TWISTS + moves.twist[t][m] as usize;
+                    if depths[to] == u8::MAX {
+                        depths[to] = depth;
+                        next.push(to);
+                    }
+                }
+            }
+            frontier = next;
+        }
+        depths
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::algorithm::Algorithm;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    #[test]
+    fn test_distances() {
+        for (metric, counts) in [
+            (
+                Metric::HalfTurn,
+                vec![
+                    1, 9, 54, 321, 1847, 9992, 50136, 227536, 870072, 1887748, 623800, 2644,
+                ],
+            ),
+            (
+                Metric::QuarterTurn,
+                vec![
+                    1, 6, 27, 120, 534, 2256, 8969, 33058, 114149, 360508, 930588, 1350852, 782536,
+                    90280, 276,
+                ],
+            ),
+        ]
+        .iter()
+        {
+            let distances = distances(*metric);
+            for (d, count) in counts.iter().enumerate() {
+                let found = distances.iter().filter(|&&x| x as usize == d).count();
+                assert_eq!(found, *count, "{:?} at {}", metric, d);
+            }
+            assert_eq!(counts.iter().sum::<usize>(), distances.len());
+        }
+    }
+
+    #[test]
+    fn test_solve() {
+        let solver = PocketSolver::new();
+        assert_eq!(solver.solve(&Cube::new2x2x2()), Ok(vec![]));
+
+        // Held any way, R U R' U' has no shorter solution.
+        let mut cube = Cube::new2x2x2();
+        cube.apply(&"x y' R U R' U' z".parse().unwrap()).unwrap();
+        assert_eq!(solver.distance(&cube), Ok(4));
+        let solution = Algorithm::from(solver.solve(&cube).unwrap());
+        assert_eq!(solution.len(), 4);
+        cube.apply(&solution).unwrap();
+        assert!(cube.is_solved());
+
+        let mut cube = Cube::new2x2x2();
+        cube.apply(&"B2 L".parse().unwrap()).unwrap();
+        let quarter = solver.clone().with_metric(Metric::QuarterTurn);
+        assert_eq!(solver.distance(&cube), Ok(2));
+        assert_eq!(quarter.distance(&cube), Ok(3));
+        let solution = Algorithm::from(quarter.solve(&cube).unwrap());
+        assert_eq!(solution.len(), 3);
+        cube.apply(&solution).unwrap();
+        assert!(cube.is_solved());
+
+        let mut rng = ChaCha8Rng::seed_from_u64(2);
+        for _ in 0..10 {
+            let mut cube = CubieCube::random_corners(&mut rng).to_cube_with_sides(2);
+            let solution = Algorithm::from(solver.solve(&cube).unwrap());
+            assert_eq!(Ok(solution.len()), solver.distance(&cube));
+            assert!(solution.len() <= 11);
+            cube.apply(&solution).unwrap();
+            assert!(cube.is_solved());
+        }
+    }
+
+    #[test]
+    fn test_solve_invalid() {
+        let solver = PocketSolver::new();
+        let twisted = Cube::from_facelets(2, "UUUF URRR FRFF DDDD LLLL BBBB").unwrap();
+        assert_eq!(solver.solve(&twisted), Err(Error::Unsolvable));
+        assert_eq!(
+            solver.distance(&Cube::new3x3x3()),
+            Err(Error::UnsupportedSides {
+                expected: 2,
+                found: 3
+            })
+        );
+    }
+}
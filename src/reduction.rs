@@ -0,0 +1,1037 @@
+use crate::{
+    algorithm::{layer_turns, Algorithm},
+    cube::Cube,
+    cubie::{face_turn, is_odd, CubieCube, FACE_TURNS},
+    error::Error,
+    facelet::{Color, Face, Facelets},
+    movement::{parse_moves, Axis, Layer, LayerInner, Move, MoveType},
+    two_phase::TwoPhaseSolver,
+};
+use nalgebra::{Matrix3, Vector3};
+use std::{
+    collections::{HashMap, VecDeque},
+    fmt,
+    ops::Range,
+};
+
+/// Flipping the pair of edges at the front of the top, see [`inner_turns`]
+/// for the lower case letters.
+const OLL_PARITY: &str = "r2 B2 U2 l U2 r' U2 r U2 F2 r F2 l' B2 r2";
+
+/// Swapping the pairs of edges at the front and the back of the top.
+const PLL_PARITY: &str = "r2 U2 r2 u2 U2 r2 u2";
+
+/// The pieces of one orbit, the places any of them can be moved to.
+const PLACES: usize = 24;
+
+/// A stage of solving a big cube by reducing it to a 3x3x3.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ReductionStage {
+    /// Every center piece on the side of its color.
+    Centers,
+    /// The pieces of each edge paired up so they show the same colors.
+    Edges,
+    /// Flipping a single pair of edges, which can't be done on a 3x3x3.
+    OllParity,
+    /// Swapping two pairs of edges, which can't be done on a 3x3x3 without
+    /// also swapping two corners.
+    PllParity,
+    /// Solving the cube as a 3x3x3 by turning only the sides.
+    ThreeByThree,
+}
+
+impl fmt::Display for ReductionStage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReductionStage::Centers => write!(f, "Centers"),
+            ReductionStage::Edges => write!(f, "Edges"),
+            ReductionStage::OllParity => write!(f, "OLL parity"),
+            ReductionStage::PllParity => write!(f, "PLL parity"),
+            ReductionStage::ThreeByThree => write!(f, "3x3x3"),
+        }
+    }
+}
+
+/// The moves solving one stage of the reduction method.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ReductionStep {
+    stage: ReductionStage,
+    moves: Vec<Move>,
+}
+
+impl ReductionStep {
+    /// The stage solved by the moves.
+    pub fn stage(&self) -> ReductionStage {
+        self.stage
+    }
+
+    /// The moves solving the stage, none if it was already solved.
+    pub fn moves(&self) -> &[Move] {
+        &self.moves
+    }
+
+    /// The number of moves, not counting whole cube rotations.
+    pub fn len(&self) -> usize {
+        self.moves
+            .iter()
+            .filter(|mv| mv.affected_range != LayerInner::WholeCube)
+            .count()
+    }
+
+    /// Whether the stage was already solved.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// A solution found by reducing a big cube to a 3x3x3, one step per stage.
+///
+/// Shown one stage per line with the number of moves of each.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ReductionSolution {
+    steps: Vec<ReductionStep>,
+}
+
+impl ReductionSolution {
+    /// The steps in the order they are done: the centers, the edges, both
+    /// parities and the 3x3x3.
+    pub fn steps(&self) -> &[ReductionStep] {
+        &self.steps
+    }
+
+    /// Every move of the solution in order.
+    pub fn moves(&self) -> Vec<Move> {
+        self.steps
+            .iter()
+            .flat_map(|s| s.moves.iter().copied())
+            .collect()
+    }
+
+    /// The number of moves, not counting whole cube rotations.
+    pub fn len(&self) -> usize {
+        self.steps.iter().map(ReductionStep::len).sum()
+    }
+
+    /// Whether the cube was already solved.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl fmt::Display for ReductionSolution {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for step in &self.steps {
+            let moves = Algorithm::from(step.moves.clone());
+            writeln!(f, "{} ({}): {}", step.stage, step.len(), moves)?;
+        }
+        write!(f, "Total: {}", self.len())
+    }
+}
+
+/// Solves cubes of any size by reducing them to a 3x3x3.
+///
+/// The center pieces are put on their sides and the pieces of each edge are
+/// paired up, one orbit of pieces at a time with three cycles made of a slice
+/// turn and a few side turns. The cube then turns like a 3x3x3 except for the
+/// parities of cubes with an even number of sides: a single flipped pair of
+/// edges (OLL parity) or two swapped pairs (PLL parity), each fixed with an
+/// algorithm turning the inner half of the cube. What is left is solved with
+/// [`TwoPhaseSolver`]. Solutions are long, growing with the square of the
+/// number of sides.
+#[derive(Debug, Clone, Default)]
+pub struct ReductionSolver {
+    three_by_three: TwoPhaseSolver,
+}
+
+impl ReductionSolver {
+    /// Create a reduction solver finishing with the default two-phase solver.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Finish with a differently configured two-phase solver.
+    pub fn with_three_by_three(mut self, solver: TwoPhaseSolver) -> Self {
+        self.three_by_three = solver;
+        self
+    }
+
+    /// The moves solving each stage of a cube with 3 or more sides.
+    ///
+    /// Returns [`Error::InvalidNumberSides`] for a 2x2x2, which has nothing to
+    /// reduce, [`Error::InvalidCenters`] if the middle centers are not arranged
+    /// like on a real cube, and [`Error::Unsolvable`] if the cube can't be
+    /// solved by turning the layers.
+    pub fn solve(&self, cube: &Cube) -> Result<ReductionSolution, Error> {
+        let sides = cube.sides();
+        if sides < 3 {
+            return Err(Error::InvalidNumberSides(sides));
+        }
+        cube.orientation()?;
+        let mut reducing = Reducing::new(cube);
+        let mut steps = Vec::with_capacity(5);
+
+        let wings: Vec<Orbit> = (1..sides / 2)
+            .map(|k| Orbit::wings(&reducing.shape, k))
+            .collect();
+        let edges = reducing.edge_colors(wings.first())?;
+        for (orbit, k) in wings.iter().zip(1..) {
+            // Three cycles only make even permutations, a slice turn makes
+            // the odd ones.
+            if is_odd(&reducing.wing_permutation(orbit, &edges)?) {
+                reducing.apply(&[Move::rotate_right(Layer::Single(k), MoveType::Clockwise)]);
+            }
+        }
+        let centers = reducing.center_colors();
+        let orbits = Orbit::centers(&reducing.shape);
+        let mut colors: Vec<Vec<Color>> = orbits
+            .iter()
+            .map(|orbit| orbit.places.iter().map(|&s| reducing.colors[s]).collect())
+            .collect();
+        // Three cycles of centers leave the other orbits alone, so only the
+        // edges need following from here on.
+        reducing.centers = false;
+        for (orbit, at) in orbits.iter().zip(&mut colors) {
+            let want: Vec<Color> = orbit
+                .places
+                .iter()
+                .map(|&s| centers[reducing.shape.face(s) as usize])
+                .collect();
+            for cycle in cycles(at, &want) {
+                reducing.apply(&orbit.three_cycle(cycle));
+            }
+        }
+        reducing.solve_centers(&centers);
+        steps.push(reducing.take(ReductionStage::Centers)?);
+
+        for orbit in &wings {
+            let mut at = reducing.wings(orbit, |s| reducing.colors[s]);
+            let want = reducing.wings(orbit, |s| reducing.shape.edge_color(s, &edges));
+            for cycle in cycles(&mut at, &want) {
+                reducing.apply(&orbit.three_cycle(cycle));
+            }
+        }
+        steps.push(reducing.take(ReductionStage::Edges)?);
+
+        let even = sides % 2 == 0;
+        let mut state = CubieCube::from_cube(&reducing.three_by_three(&centers)?)?;
+        if even && state.eo.iter().sum::<u8>() % 2 == 1 {
+            reducing.apply(&inner_turns(OLL_PARITY, sides));
+            state = CubieCube::from_cube(&reducing.three_by_three(&centers)?)?;
+        }
+        steps.push(reducing.take(ReductionStage::OllParity)?);
+        if even && is_odd(&state.cp) != is_odd(&state.ep) {
+            reducing.apply(&inner_turns(PLL_PARITY, sides));
+            state = CubieCube::from_cube(&reducing.three_by_three(&centers)?)?;
+        }
+        steps.push(reducing.take(ReductionStage::PllParity)?);
+
+        reducing.apply(&self.three_by_three.solve_cubies(&state)?);
+        steps.push(reducing.take(ReductionStage::ThreeByThree)?);
+        Ok(ReductionSolution { steps })
+    }
+}
+
+/// The moves of an algorithm, lower case letters turning the layers from the
+/// second to the middle of the cube.
+fn inner_turns(notation: &str, sides: usize) -> Vec<Move> {
+    let mut moves = Vec::new();
+    for token in notation.split_whitespace() {
+        let outer = parse_moves(&token.to_uppercase()).expect("algorithms are valid notation");
+        if token.starts_with(char::is_lowercase) {
+            for mv in outer {
+                moves.push(Move {
+                    affected_range: LayerInner::Multiple(sides / 2),
+                    ..mv
+                });
+                moves.push(mv.inverse());
+            }
+        } else {
+            moves.extend(outer);
+        }
+    }
+    moves
+}
+
+/// A layer turn as the quarter turns around a positive axis.
+struct Turn {
+    axis: usize,
+    layers: Range<usize>,
+    rotation: Matrix3<isize>,
+}
+
+impl Turn {
+    fn new(mv: &Move, sides: usize) -> Self {
+        let (axis, layers, turns) = layer_turns(mv, sides).expect("moves fit the cube");
+        let quarters = MoveType::from_quarter_turns(turns).expect("layer turns always turn");
+        Turn {
+            axis: match axis {
+                Axis::X => 0,
+                Axis::Y => 1,
+                Axis::Z => 2,
+            },
+            layers,
+            rotation: *Move::rotate_cube(axis, quarters).rotation_matrix(),
+        }
+    }
+}
+
+/// Where the stickers of a cube are, numbered like [`Facelets`], and where
+/// turns move them.
+///
+/// Positions are doubled so cubes with an odd and an even number of sides
+/// both have whole numbers at the middle of every piece.
+struct Shape {
+    sides: usize,
+}
+
+impl Shape {
+    fn sticker(&self, face: Face, row: usize, col: usize) -> usize {
+        (face as usize * self.sides + row) * self.sides + col
+    }
+
+    fn face(&self, sticker: usize) -> Face {
+        Face::ALL[sticker / (self.sides * self.sides)]
+    }
+
+    fn row_col(&self, sticker: usize) -> (usize, usize) {
+        let i = sticker % (self.sides * self.sides);
+        (i / self.sides, i % self.sides)
+    }
+
+    fn position(&self, sticker: usize) -> Vector3<isize> {
+        let face = self.face(sticker);
+        let (row, col) = self.row_col(sticker);
+        let last = self.sides as isize - 1;
+        face.normal() * last
+            + face.right() * (2 * col as isize - last)
+            + face.down() * (2 * row as isize - last)
+    }
+
+    fn at(&self, position: &Vector3<isize>, face: Face) -> usize {
+        let last = self.sides as isize - 1;
+        let row = (position.dot(&face.down()) + last) / 2;
+        let col = (position.dot(&face.right()) + last) / 2;
+        self.sticker(face, row as usize, col as usize)
+    }
+
+    /// The layer a position is in, counted from the positive end of an axis.
+    fn layer(&self, position: &Vector3<isize>, axis: usize) -> usize {
+        ((self.sides as isize - 1 - position[axis]) / 2) as usize
+    }
+
+    fn turn(&self, sticker: usize, turn: &Turn) -> usize {
+        let position = self.position(sticker);
+        if !turn.layers.contains(&self.layer(&position, turn.axis)) {
+            return sticker;
+        }
+        let normal = turn.rotation * self.face(sticker).normal();
+        let face = Face::from_normal(&normal).expect("turns are quarter turns");
+        self.at(&(turn.rotation * position), face)
+    }
+
+    fn turned(&self, sticker: usize, turns: &[Turn]) -> usize {
+        turns.iter().fold(sticker, |s, t| self.turn(s, t))
+    }
+
+    fn is_center(&self, sticker: usize) -> bool {
+        let (row, col) = self.row_col(sticker);
+        let inner = 1..self.sides - 1;
+        inner.contains(&row) && inner.contains(&col)
+    }
+
+    fn is_edge(&self, sticker: usize) -> bool {
+        let (row, col) = self.row_col(sticker);
+        let inner = 1..self.sides - 1;
+        !self.is_center(sticker) && (inner.contains(&row) || inner.contains(&col))
+    }
+
+    /// The stickers moved both by a turn and by a sequence of moves.
+    fn crossed(&self, a: &Move, b: &[Move]) -> Vec<usize> {
+        let b: Vec<Turn> = b.iter().map(|mv| Turn::new(mv, self.sides)).collect();
+        self.moved(&Turn::new(a, self.sides), true)
+            .into_iter()
+            .filter(|&s| self.turned(s, &b) != s)
+            .collect()
+    }
+
+    /// The stickers a turn moves, only those of corners and edges unless
+    /// `centers` is set.
+    fn moved(&self, turn: &Turn, centers: bool) -> Vec<usize> {
+        let n = self.sides;
+        let last = n - 1;
+        let edge = |i: usize| i == 0 || i == last;
+        let mut moved = Vec::new();
+        for &face in Face::ALL.iter() {
+            let normal = face.normal();
+            if normal[turn.axis] != 0 {
+                if turn
+                    .layers
+                    .contains(&self.layer(&(normal * last as isize), turn.axis))
+                {
+                    for row in 0..n {
+                        for col in 0..n {
+                            if centers || edge(row) || edge(col) {
+                                moved.push(self.sticker(face, row, col));
+                            }
+                        }
+                    }
+                }
+                continue;
+            }
+            // The rows or columns of the side in the turned layers.
+            let across = face.right()[turn.axis] != 0;
+            for line in 0..n {
+                let (row, col) = if across { (0, line) } else { (line, 0) };
+                if !turn
+                    .layers
+                    .contains(&self.layer(&self.position(self.sticker(face, row, col)), turn.axis))
+                {
+                    continue;
+                }
+                for i in 0..n {
+                    if centers || edge(line) || edge(i) {
+                        let (row, col) = if across { (i, line) } else { (line, i) };
+                        moved.push(self.sticker(face, row, col));
+                    }
+                }
+            }
+        }
+        moved
+    }
+
+    /// The other sticker of the edge piece a sticker is on.
+    fn partner(&self, sticker: usize) -> usize {
+        let position = self.position(sticker);
+        let normal = self.face(sticker).normal();
+        let last = self.sides as isize - 1;
+        let axis = (0..3)
+            .find(|&a| normal[a] == 0 && position[a].abs() == last)
+            .expect("edge pieces have two stickers");
+        let mut other = Vector3::zeros();
+        other[axis] = position[axis].signum();
+        self.at(
+            &position,
+            Face::from_normal(&other).expect("axes point out of sides"),
+        )
+    }
+
+    /// The colors of the two stickers of an edge piece, swapped if needed so
+    /// the order stays the same however the piece is turned.
+    fn ordered(&self, sticker: usize, colors: (Color, Color)) -> (Color, Color) {
+        let first = self.face(sticker).normal();
+        let second = self.face(self.partner(sticker)).normal();
+        if self.position(sticker).dot(&first.cross(&second)) > 0 {
+            colors
+        } else {
+            (colors.1, colors.0)
+        }
+    }
+
+    /// The color a sticker of an edge should have once the edge is solved.
+    fn edge_color(&self, sticker: usize, edges: &EdgeColors) -> Color {
+        edges[self.face(sticker) as usize][self.face(self.partner(sticker)) as usize]
+            .expect("edges are between neighbouring sides")
+    }
+}
+
+/// The pieces of a big cube that can be moved between each other's places
+/// and a three cycle moving them.
+struct Orbit {
+    /// A sticker of the piece at each place.
+    places: Vec<usize>,
+    /// The place of every sticker of the pieces.
+    index: HashMap<usize, u8>,
+    /// Turns setting up the three cycle, with where each moves the piece at
+    /// each place.
+    setups: Vec<(Move, [u8; PLACES])>,
+    /// Moves cycling three places of the orbit and no other pieces of the
+    /// same kind.
+    cycle: Vec<Move>,
+    /// The first setup for every three places, see [`three`].
+    first: Vec<u8>,
+}
+
+/// Marks the places cycled in [`Orbit::first`].
+const CYCLED: u8 = u8::MAX - 1;
+
+/// Marks three places not reached in [`Orbit::first`].
+const UNREACHED: u8 = u8::MAX;
+
+/// The index of three places in [`Orbit::first`].
+fn three(places: [u8; 3]) -> usize {
+    (places[0] as usize * PLACES + places[1] as usize) * PLACES + places[2] as usize
+}
+
+impl Orbit {
+    /// Every orbit of center pieces, leaving out the fixed centers of cubes
+    /// with an odd number of sides.
+    fn centers(shape: &Shape) -> Vec<Orbit> {
+        let last = shape.sides - 1;
+        let turn = |(row, col): (usize, usize)| (col, last - row);
+        let mut orbits = Vec::new();
+        for row in 1..last {
+            for col in 1..last {
+                let mut quarters = [(row, col); 4];
+                for i in 1..4 {
+                    quarters[i] = turn(quarters[i - 1]);
+                }
+                // Each orbit once, from the first of its places on a side.
+                if quarters[1..].iter().any(|&q| q <= (row, col)) {
+                    continue;
+                }
+                let places = Face::ALL
+                    .iter()
+                    .flat_map(|&face| quarters.iter().map(move |&(r, c)| (face, r, c)))
+                    .map(|(face, r, c)| shape.sticker(face, r, c))
+                    .collect();
+                let mut layers = vec![row, col, last - row, last - col];
+                layers.sort_unstable();
+                layers.dedup();
+                // A slice through a column of the bottom and one through a
+                // row of it, with the top turned so they only cross there.
+                let mut candidates = Vec::new();
+                for &a in &layers {
+                    for &b in &layers {
+                        for &q in [MoveType::Clockwise, MoveType::CounterClockwise].iter() {
+                            let a = Move::rotate_right(Layer::Single(a), MoveType::Clockwise);
+                            let b = [
+                                Move::rotate_top(Layer::Single(0), q),
+                                Move::rotate_front(Layer::Single(b), MoveType::Clockwise),
+                                Move::rotate_top(Layer::Single(0), q.opposite()),
+                            ];
+                            let crossed = shape.crossed(&a, &b);
+                            if crossed.iter().filter(|&&s| shape.is_center(s)).count() == 1 {
+                                candidates.push(commutator(a, &b));
+                            }
+                        }
+                    }
+                }
+                orbits.push(Orbit::new(shape, places, &layers, candidates));
+            }
+        }
+        orbits
+    }
+
+    /// The orbit of edge pieces `k` places from either end of their edges.
+    fn wings(shape: &Shape, k: usize) -> Orbit {
+        let last = shape.sides - 1;
+        let mut places = Vec::new();
+        for &face in Face::ALL.iter() {
+            for &i in [k, last - k].iter() {
+                for &(row, col) in [(0, i), (last, i), (i, 0), (i, last)].iter() {
+                    let sticker = shape.sticker(face, row, col);
+                    // One sticker of each piece, on the first of its sides.
+                    if (face as usize) < shape.face(shape.partner(sticker)) as usize {
+                        places.push(sticker);
+                    }
+                }
+            }
+        }
+        // A slice taking a piece out of the top and a side turn putting
+        // another in, crossing at that piece only.
+        let mut candidates = Vec::new();
+        for &a in [k, last - k].iter() {
+            for &q in [MoveType::Clockwise, MoveType::CounterClockwise].iter() {
+                for &r in [MoveType::Clockwise, MoveType::CounterClockwise].iter() {
+                    let a = Move::rotate_right(Layer::Single(a), MoveType::Clockwise);
+                    let b = [
+                        Move::rotate_top(Layer::Single(0), q),
+                        Move::rotate_right(Layer::Single(0), r),
+                        Move::rotate_top(Layer::Single(0), q.opposite()),
+                    ];
+                    if shape.crossed(&a, &b).len() == 2 {
+                        candidates.push(commutator(a, &b));
+                    }
+                }
+            }
+        }
+        Orbit::new(shape, places, &[k, last - k], candidates)
+    }
+
+    /// The orbit using the first candidate that is a three cycle of its
+    /// places, set up with side turns and turns of the slices through it.
+    fn new(
+        shape: &Shape,
+        places: Vec<usize>,
+        layers: &[usize],
+        candidates: Vec<Vec<Move>>,
+    ) -> Orbit {
+        let mut index = HashMap::new();
+        for (i, &sticker) in places.iter().enumerate() {
+            index.insert(sticker, i as u8);
+            if shape.is_edge(sticker) {
+                index.insert(shape.partner(sticker), i as u8);
+            }
+        }
+        let mut orbit = Orbit {
+            places,
+            index,
+            setups: Vec::new(),
+            cycle: Vec::new(),
+            first: vec![UNREACHED; PLACES * PLACES * PLACES],
+        };
+        let slices = layers.iter().flat_map(|&l| {
+            [Move::rotate_top, Move::rotate_right, Move::rotate_front]
+                .iter()
+                .flat_map(move |turn| {
+                    [
+                        MoveType::Clockwise,
+                        MoveType::Twice,
+                        MoveType::CounterClockwise,
+                    ]
+                    .iter()
+                    .map(move |&q| turn(Layer::Single(l), q))
+                })
+        });
+        orbit.setups = (0..FACE_TURNS)
+            .map(face_turn)
+            .chain(slices)
+            .map(|mv| (mv, orbit.permutation(shape, &[mv])))
+            .collect();
+        let (cycle, cycled) = candidates
+            .into_iter()
+            .find_map(|moves| {
+                let moved = orbit.permutation(shape, &moves);
+                let mut cycled = (0..PLACES).filter(|&i| moved[i] as usize != i);
+                let first = cycled.next()? as u8;
+                let (second, third) =
+                    (moved[first as usize], moved[moved[first as usize] as usize]);
+                (cycled.count() == 2 && moved[third as usize] == first)
+                    .then_some((moves, [first, second, third]))
+            })
+            .expect("every orbit has a three cycle");
+        orbit.cycle = cycle;
+        orbit.search(cycled);
+        orbit
+    }
+
+    /// Where the piece at each place goes with the moves.
+    fn permutation(&self, shape: &Shape, moves: &[Move]) -> [u8; PLACES] {
+        let turns: Vec<Turn> = moves.iter().map(|mv| Turn::new(mv, shape.sides)).collect();
+        let mut moved = [0; PLACES];
+        for (to, &sticker) in moved.iter_mut().zip(&self.places) {
+            *to = self.index[&shape.turned(sticker, &turns)];
+        }
+        moved
+    }
+
+    /// Fill in the first setup for every three places, searching back from
+    /// the places cycled.
+    fn search(&mut self, cycled: [u8; 3]) {
+        let undo: Vec<[u8; PLACES]> = self
+            .setups
+            .iter()
+            .map(|(_, moved)| {
+                let mut back = [0; PLACES];
+                for (i, &to) in moved.iter().enumerate() {
+                    back[to as usize] = i as u8;
+                }
+                back
+            })
+            .collect();
+        self.first[three(cycled)] = CYCLED;
+        let mut queue = VecDeque::from(vec![cycled]);
+        while let Some(places) = queue.pop_front() {
+            for (setup, back) in undo.iter().enumerate() {
+                let from = places.map(|p| back[p as usize]);
+                if self.first[three(from)] == UNREACHED {
+                    self.first[three(from)] = setup as u8;
+                    queue.push_back(from);
+                }
+            }
+        }
+    }
+
+    /// Moves taking the piece at the first place to the second, the second
+    /// to the third and the third to the first.
+    fn three_cycle(&self, places: [usize; 3]) -> Vec<Move> {
+        let mut places = places.map(|p| p as u8);
+        let mut setup = Vec::new();
+        loop {
+            match self.first[three(places)] {
+                CYCLED => break,
+                UNREACHED => unreachable!("setups reach any three places"),
+                s => {
+                    let (mv, moved) = &self.setups[s as usize];
+                    setup.push(*mv);
+                    places = places.map(|p| moved[p as usize]);
+                }
+            }
+        }
+        let mut moves = setup.clone();
+        moves.extend_from_slice(&self.cycle);
+        moves.extend(setup.iter().rev().map(Move::inverse));
+        moves
+    }
+}
+
+/// The commutator `A B A' B'`.
+fn commutator(a: Move, b: &[Move]) -> Vec<Move> {
+    let mut moves = vec![a];
+    moves.extend_from_slice(b);
+    moves.push(a.inverse());
+    moves.extend(b.iter().rev().map(Move::inverse));
+    moves
+}
+
+/// A big cube being reduced, as the color of every sticker.
+struct Reducing {
+    shape: Shape,
+    colors: Vec<Color>,
+    /// Whether the stickers of center pieces are kept up to date.
+    centers: bool,
+    moves: Vec<Move>,
+}
+
+impl Reducing {
+    fn new(cube: &Cube) -> Self {
+        Reducing {
+            shape: Shape {
+                sides: cube.sides(),
+            },
+            colors: cube.to_facelets().stickers().to_vec(),
+            centers: true,
+            moves: Vec::new(),
+        }
+    }
+
+    fn apply(&mut self, moves: &[Move]) {
+        for mv in moves {
+            let turn = Turn::new(mv, self.shape.sides);
+            let moved: Vec<(usize, Color)> = self
+                .shape
+                .moved(&turn, self.centers)
+                .into_iter()
+                .map(|s| (self.shape.turn(s, &turn), self.colors[s]))
+                .collect();
+            for (s, color) in moved {
+                self.colors[s] = color;
+            }
+        }
+        self.moves.extend_from_slice(moves);
+    }
+
+    /// The moves made since the last call as the step solving a stage.
+    fn take(&mut self, stage: ReductionStage) -> Result<ReductionStep, Error> {
+        let moves = Algorithm::from(std::mem::take(&mut self.moves)).simplify(self.shape.sides)?;
+        Ok(ReductionStep {
+            stage,
+            moves: moves.into(),
+        })
+    }
+
+    /// The color of each side once solved, that of its center on cubes with
+    /// an odd number of sides.
+    fn center_colors(&self) -> [Color; 6] {
+        let n = self.shape.sides;
+        Face::ALL.map(|face| {
+            if n % 2 == 0 {
+                face.color()
+            } else {
+                self.colors[self.shape.sticker(face, n / 2, n / 2)]
+            }
+        })
+    }
+
+    fn solve_centers(&mut self, centers: &[Color; 6]) {
+        for s in 0..self.colors.len() {
+            if self.shape.is_center(s) {
+                self.colors[s] = centers[self.shape.face(s) as usize];
+            }
+        }
+        self.centers = true;
+    }
+
+    /// The color each side shows on the edge it shares with each other side
+    /// once the edges are paired up.
+    ///
+    /// These are the colors of the middle pieces on cubes with an odd number
+    /// of sides. Otherwise a piece of the first orbit keeps its colors where
+    /// no other edge has them already, with one edge flipped if needed so the
+    /// orbit can be solved with three cycles.
+    fn edge_colors(&self, first: Option<&Orbit>) -> Result<EdgeColors, Error> {
+        let shape = &self.shape;
+        let n = shape.sides;
+        let mut edges = [[None; 6]; 6];
+        let orbit = match first {
+            Some(orbit) if n % 2 == 0 => orbit,
+            _ => {
+                for &face in Face::ALL.iter() {
+                    for &(row, col) in
+                        [(0, n / 2), (n - 1, n / 2), (n / 2, 0), (n / 2, n - 1)].iter()
+                    {
+                        let s = shape.sticker(face, row, col);
+                        edges[face as usize][shape.face(shape.partner(s)) as usize] =
+                            Some(self.colors[s]);
+                    }
+                }
+                return Ok(edges);
+            }
+        };
+        let mut used = Vec::new();
+        for &s in &orbit.places {
+            let p = shape.partner(s);
+            let (face, other) = (shape.face(s) as usize, shape.face(p) as usize);
+            let colors = (self.colors[s], self.colors[p]);
+            if edges[face][other].is_none() && !used.contains(&colors) {
+                used.extend_from_slice(&[colors, (colors.1, colors.0)]);
+                edges[face][other] = Some(colors.0);
+                edges[other][face] = Some(colors.1);
+            }
+        }
+        // The colors left over go to the edges left over.
+        let mut left = EDGES
+            .iter()
+            .map(|&(face, other)| (Face::ALL[face].color(), Face::ALL[other].color()))
+            .filter(|colors| !used.contains(colors));
+        for &(face, other) in EDGES.iter() {
+            if edges[face][other].is_none() {
+                let colors = left.next().expect("as many colors as edges are left");
+                edges[face][other] = Some(colors.0);
+                edges[other][face] = Some(colors.1);
+            }
+        }
+        if is_odd(&self.wing_permutation(orbit, &edges)?) {
+            let (face, other) = EDGES[0];
+            let flipped = edges[face][other];
+            edges[face][other] = edges[other][face];
+            edges[other][face] = flipped;
+        }
+        Ok(edges)
+    }
+
+    /// The colors of the pieces at each place of an orbit of edges, in the
+    /// order that stays the same when they are turned.
+    fn wings(&self, orbit: &Orbit, color: impl Fn(usize) -> Color) -> Vec<(Color, Color)> {
+        orbit
+            .places
+            .iter()
+            .map(|&s| {
+                self.shape
+                    .ordered(s, (color(s), color(self.shape.partner(s))))
+            })
+            .collect()
+    }
+
+    /// The place each piece of an orbit of edges has to go to.
+    fn wing_permutation(&self, orbit: &Orbit, edges: &EdgeColors) -> Result<Vec<u8>, Error> {
+        let want = self.wings(orbit, |s| self.shape.edge_color(s, edges));
+        let mut permutation = Vec::with_capacity(PLACES);
+        for piece in self.wings(orbit, |s| self.colors[s]) {
+            match want.iter().position(|&w| w == piece) {
+                Some(to) if !permutation.contains(&(to as u8)) => permutation.push(to as u8),
+                _ => return Err(Error::Unsolvable),
+            }
+        }
+        Ok(permutation)
+    }
+
+    /// The cube turning like it once the centers are solved and the edges
+    /// paired up, as a 3x3x3.
+    fn three_by_three(&self, centers: &[Color; 6]) -> Result<Cube, Error> {
+        let last = self.shape.sides - 1;
+        let layer = [0, 1, last];
+        let mut stickers = Vec::with_capacity(54);
+        for &face in Face::ALL.iter() {
+            for &row in &layer {
+                for &col in &layer {
+                    stickers.push(if (row, col) == (1, 1) {
+                        centers[face as usize]
+                    } else {
+                        self.colors[self.shape.sticker(face, row, col)]
+                    });
+                }
+            }
+        }
+        Cube::from_facelets(3, &Facelets::new(3, stickers).to_string())
+    }
+}
+
+/// The color each side shows on the edge it shares with each of its
+/// neighbours, indexed like [`Face::ALL`].
+type EdgeColors = [[Option<Color>; 6]; 6];
+
+/// Pairs of sides sharing an edge, by their index in [`Face::ALL`].
+const EDGES: [(usize, usize); 12] = [
+    (0, 1),
+    (0, 2),
+    (0, 4),
+    (0, 5),
+    (1, 2),
+    (1, 3),
+    (1, 5),
+    (2, 3),
+    (2, 4),
+    (3, 4),
+    (3, 5),
+    (4, 5),
+];
+
+/// Three cycles, each moving the piece at the first place to the second, the
+/// second to the third and the third to the first, putting pieces where they
+/// are wanted. Pieces that look the same can take each other's places.
+fn cycles<T: Copy + Eq>(at: &mut [T], want: &[T]) -> Vec<[usize; 3]> {
+    let mut cycles = Vec::new();
+    while let Some(x) = (0..at.len()).find(|&x| at[x] != want[x]) {
+        let y = (0..at.len())
+            .find(|&y| at[y] == want[x] && at[y] != want[y])
+            .expect("every piece has a place");
+        // The third place fixes as many pieces as possible.
+        let z = (0..at.len())
+            .filter(|&z| z != x && z != y && at[z] != want[z])
+            .max_by_key(|&z| usize::from(want[z] == at[x]) + usize::from(at[z] == want[y]))
+            .or_else(|| (0..at.len()).find(|&z| z != x && z != y && at[z] == at[x]))
+            .expect("only even permutations are cycled");
+        let moved = at[z];
+        at[z] = at[x];
+        at[x] = at[y];
+        at[y] = moved;
+        cycles.push([y, x, z]);
+    }
+    cycles
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::scramble::MoveScrambler;
+
+    fn centers_solved(cube: &Cube) -> bool {
+        let n = cube.sides();
+        let facelets = cube.to_facelets();
+        Face::ALL.iter().all(|&face| {
+            (1..n - 1).all(|row| {
+                (1..n - 1).all(|col| facelets.get(face, row, col) == facelets.get(face, 1, 1))
+            })
+        })
+    }
+
+    fn edges_paired(cube: &Cube) -> bool {
+        let n = cube.sides();
+        let facelets = cube.to_facelets();
+        Face::ALL.iter().all(|&face| {
+            (1..n - 1).all(|i| {
+                facelets.get(face, 0, i) == facelets.get(face, 0, 1)
+                    && facelets.get(face, n - 1, i) == facelets.get(face, n - 1, 1)
+                    && facelets.get(face, i, 0) == facelets.get(face, 1, 0)
+                    && facelets.get(face, i, n - 1) == facelets.get(face, 1, n - 1)
+            })
+        })
+    }
+
+    fn stages(solution: &ReductionSolution) -> Vec<ReductionStage> {
+        solution.steps().iter().map(ReductionStep::stage).collect()
+    }
+
+    #[test]
+    fn test_three_cycles() {
+        for sides in 4..=7 {
+            let shape = Shape { sides };
+            let moved = |moves: &[Move]| {
+                let turns: Vec<Turn> = moves.iter().map(|mv| Turn::new(mv, sides)).collect();
+                (0..6 * sides * sides)
+                    .filter(|&s| shape.turned(s, &turns) != s)
+                    .collect::<Vec<_>>()
+            };
+            for orbit in Orbit::centers(&shape) {
+                let centers: Vec<usize> = moved(&orbit.cycle)
+                    .into_iter()
+                    .filter(|&s| shape.is_center(s))
+                    .collect();
+                assert_eq!(centers.len(), 3);
+                assert!(centers.iter().all(|s| orbit.places.contains(s)));
+            }
+            for k in 1..sides / 2 {
+                let orbit = Orbit::wings(&shape, k);
+                // Three pieces with two stickers each and nothing else.
+                let stickers = moved(&orbit.cycle);
+                assert_eq!(stickers.len(), 6);
+                assert!(stickers.iter().all(|s| orbit.index.contains_key(s)));
+                let cycle = orbit.three_cycle([3, 17, 8]);
+                let turns: Vec<Turn> = cycle.iter().map(|mv| Turn::new(mv, sides)).collect();
+                assert_eq!(orbit.index[&shape.turned(orbit.places[3], &turns)], 17);
+                assert_eq!(moved(&cycle).len(), 6);
+            }
+        }
+    }
+
+    #[test]
+    fn test_solve() {
+        let solver = ReductionSolver::new();
+        for sides in 3..=7 {
+            for seed in 0..3 {
+                let scramble = MoveScrambler::from_seed(sides, seed).unwrap().scramble();
+                let mut cube = Cube::with_number_sides(sides).unwrap();
+                cube.apply(&scramble).unwrap();
+                let solution = solver.solve(&cube).unwrap();
+                assert_eq!(
+                    stages(&solution),
+                    vec![
+                        ReductionStage::Centers,
+                        ReductionStage::Edges,
+                        ReductionStage::OllParity,
+                        ReductionStage::PllParity,
+                        ReductionStage::ThreeByThree,
+                    ]
+                );
+                for step in solution.steps() {
+                    cube.apply(&step.moves().to_vec().into()).unwrap();
+                    match step.stage() {
+                        ReductionStage::Centers => assert!(centers_solved(&cube)),
+                        ReductionStage::Edges => assert!(edges_paired(&cube)),
+                        _ => {}
+                    }
+                }
+                assert!(cube.is_solved(), "{} sides, seed {}", sides, seed);
+                if sides % 2 == 1 {
+                    assert!(solution.steps()[2].is_empty() && solution.steps()[3].is_empty());
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_parity() {
+        let solver = ReductionSolver::new();
+        for sides in [4, 6].iter().copied() {
+            for (alg, parity) in [(OLL_PARITY, 2), (PLL_PARITY, 3)].iter().copied() {
+                let mut cube = Cube::with_number_sides(sides).unwrap();
+                cube.apply(&inner_turns(alg, sides).into()).unwrap();
+                let solution = solver.solve(&cube).unwrap();
+                let turned: Vec<usize> = (0..5)
+                    .filter(|&i| !solution.steps()[i].is_empty())
+                    .collect();
+                assert_eq!(turned, vec![parity]);
+                cube.apply(&solution.moves().into()).unwrap();
+                assert!(cube.is_solved());
+            }
+        }
+    }
+
+    #[test]
+    fn test_unsolvable() {
+        let solver = ReductionSolver::new();
+        assert_eq!(
+            solver.solve(&Cube::new2x2x2()),
+            Err(Error::InvalidNumberSides(2))
+        );
+        let mut facelets: Vec<char> = Cube::with_number_sides(4)
+            .unwrap()
+            .to_facelets()
+            .to_string()
+            .chars()
+            .collect();
+        // Twist the corner between the top, right and front.
+        let (up, right, front) = (facelets[15], facelets[16], facelets[35]);
+        facelets[15] = right;
+        facelets[16] = front;
+        facelets[35] = up;
+        let twisted: String = facelets.into_iter().collect();
+        let cube = Cube::from_facelets(4, &twisted).unwrap();
+        assert_eq!(solver.solve(&cube), Err(Error::Unsolvable));
+        assert!(solver
+            .solve(&Cube::with_number_sides(5).unwrap())
+            .unwrap()
+            .is_empty());
+    }
+}
@@ -0,0 +1,292 @@
+use crate::{
+    algorithm::Algorithm,
+    cube::Cube,
+    cubie::{face_turn, CubieCube},
+    error::Error,
+    movement::{AxisInner, Layer, Move, MoveType},
+    pocket::{self, Metric},
+    two_phase::TwoPhaseSolver,
+};
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+/// The number of moves in a WCA random move scramble for a cube of the size.
+///
+/// This is 11 for a 2x2x2, 25 for a 3x3x3 and `20 * (sides - 2)` for bigger
+/// cubes, so 40 for a 4x4x4 and 60 for a 5x5x5.
+pub fn scramble_length(sides: usize) -> usize {
+    match sides {
+        0..=2 => 11,
+        3 => 25,
+        _ => 20 * (sides - 2),
+    }
+}
+
+/// Makes scrambles by turning random layers of the cube.
+///
+/// Cubes of 4x4x4 and up also get wide moves, up to half of the cube deep. The
+/// down, left and back sides are never turned as deep as their opposite sides
+/// so two moves on the same axis can't add up to turning the whole cube. This
+/// leaves a 2x2x2 with only up, right and front moves.
+///
+/// A side is never turned twice, at any depth, without a move on another axis
+/// in between, so there are no sequences like `R R'`, `R Rw'` or `R L R` that
+/// could be written with fewer face, slice or wide moves.
+#[derive(Debug, Clone)]
+pub struct MoveScrambler<R = ChaCha8Rng> {
+    rng: R,
+    length: usize,
+    moves: Vec<(AxisInner, usize)>,
+}
+
+impl MoveScrambler {
+    /// Create a scrambler that always makes the same scrambles for a seed.
+    pub fn from_seed(sides: usize, seed: u64) -> Result<Self, Error> {
+        Self::new(sides, ChaCha8Rng::seed_from_u64(seed))
+    }
+}
+
+impl<R: Rng> MoveScrambler<R> {
+    /// Create a scrambler making [`scramble_length`] moves with random numbers
+    /// from `rng`.
+    pub fn new(sides: usize, rng: R) -> Result<Self, Error> {
+        if sides < 2 {
+            return Err(Error::InvalidNumberSides(sides));
+        }
+        let axes = [
+            AxisInner::Z,
+            AxisInner::Y,
+            AxisInner::X,
+            AxisInner::NegZ,
+            AxisInner::NegY,
+            AxisInner::NegX,
+        ];
+        let moves = axes
+            .iter()
+            .flat_map(|&axis| {
+                let depth = if axis.is_negative() {
+                    (sides - 1) / 2
+                } else {
+                    sides / 2
+                };
+                (1..=depth).map(move |d| (axis, d))
+            })
+            .collect();
+        Ok(MoveScrambler {
+            rng,
+            length: scramble_length(sides),
+            moves,
+        })
+    }
+
+    /// Make scrambles with a different number of moves.
+    pub fn with_length(mut self, length: usize) -> Self {
+        self.length = length;
+        self
+    }
+
+    /// Make a new random scramble.
+    pub fn scramble(&mut self) -> Algorithm {
+        let mut alg = Algorithm::new();
+        // Sides turned since the last move on another axis.
+        let mut turned: Vec<AxisInner> = Vec::new();
+        while alg.len() < self.length {
+            let (axis, depth) = self.moves[self.rng.gen_range(0..self.moves.len())];
+            if turned.contains(&axis) {
+                continue;
+            }
+            if turned
+                .first()
+                .is_some_and(|a| a.unsigned() != axis.unsigned())
+            {
+                turned.clear();
+            }
+            turned.push(axis);
+            let move_type = match self.rng.gen_range(0..3) {
+                0 => MoveType::Clockwise,
+                1 => MoveType::Twice,
+                _ => MoveType::CounterClockwise,
+            };
+            let layer = if depth == 1 {
+                Layer::Single(0)
+            } else {
+                Layer::Multiple(depth)
+            };
+            alg.push(Move {
+                move_type,
+                axis,
+                affected_range: layer.into(),
+            });
+        }
+        alg
+    }
+}
+
+/// The most turns in a scramble made by [`StateScrambler`] for a 3x3x3.
+const STATE_SCRAMBLE_LENGTH: usize = 24;
+
+/// Makes scrambles by picking a uniformly random state of the cube and solving
+/// it, so every state is equally likely unlike with [`MoveScrambler`].
+///
+/// Only 2x2x2 and 3x3x3 cubes are supported. Scrambles for a 2x2x2 are the
+/// fewest up, right and front turns reaching the state. Scrambles for a 3x3x3
+/// use every side and are at most 24 turns, usually a few more than the fewest
+/// needed.
+#[derive(Debug, Clone)]
+pub struct StateScrambler<R = ChaCha8Rng> {
+    rng: R,
+    sides: usize,
+}
+
+impl StateScrambler {
+    /// Create a scrambler that always makes the same scrambles for a seed.
+    pub fn from_seed(sides: usize, seed: u64) -> Result<Self, Error> {
+        Self::new(sides, ChaCha8Rng::seed_from_u64(seed))
+    }
+}
+
+impl<R: Rng> StateScrambler<R> {
+    /// Create a scrambler picking states with random numbers from `rng`.
+    pub fn new(sides: usize, rng: R) -> Result<Self, Error> {
+        match sides {
+            2 | 3 => Ok(StateScrambler { rng, sides }),
+            _ => Err(Error::UnsupportedRandomState(sides)),
+        }
+    }
+
+    /// Make a new random scramble.
+    ///
+    /// Fails with the error of [`TwoPhaseSolver`] if it can't find a 3x3x3
+    /// scramble short enough, which doesn't happen for any known state.
+    pub fn scramble(&mut self) -> Result<Algorithm, Error> {
+        Ok(self.scramble_with_state()?.0)
+    }
+
+    /// Make a new random scramble along with the cube it scrambles to, see
+    /// [`StateScrambler::scramble`].
+    pub fn scramble_with_state(&mut self) -> Result<(Algorithm, Cube), Error> {
+        let (state, solution): (_, Algorithm) = match self.sides {
+            2 => {
+                let state = CubieCube::random_corners(&mut self.rng);
+                let solution = pocket::solve_cubies(&state, Metric::HalfTurn)
+                    .into_iter()
+                    .map(face_turn)
+                    .collect();
+                (state, solution)
+            }
+            _ => {
+                let state = CubieCube::random(&mut self.rng);
+                let solution = TwoPhaseSolver::new()
+                    .with_max_length(STATE_SCRAMBLE_LENGTH)
+                    .solve_cubies(&state)?;
+                (state, solution.into())
+            }
+        };
+        Ok((solution.inverse(), state.to_cube_with_sides(self.sides)))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::cube::Cube;
+
+    #[test]
+    fn test_scramble_length() {
+        assert_eq!(scramble_length(2), 11);
+        assert_eq!(scramble_length(3), 25);
+        assert_eq!(scramble_length(4), 40);
+        assert_eq!(scramble_length(5), 60);
+        assert_eq!(scramble_length(7), 100);
+    }
+
+    #[test]
+    fn test_move_scrambler() {
+        for sides in 2..=7 {
+            let mut scrambler = MoveScrambler::from_seed(sides, 7).unwrap();
+            for _ in 0..20 {
+                let scramble = scrambler.scramble();
+                assert_eq!(scramble.len(), scramble_length(sides));
+                let moves = scramble.moves();
+                // No two moves in a row turn the same side.
+                for pair in moves.windows(2) {
+                    assert_ne!(pair[0].axis, pair[1].axis, "{}", scramble);
+                }
+                for (i, mv) in moves.iter().enumerate() {
+                    let run = moves[..i]
+                        .iter()
+                        .rev()
+                        .take_while(|m| m.axis.unsigned() == mv.axis.unsigned());
+                    for m in run {
+                        assert_ne!(m.axis, mv.axis, "{}", scramble);
+                    }
+                }
+                assert_eq!(scramble.simplify(sides).unwrap().len(), scramble.len());
+                let mut cube = Cube::with_number_sides(sides).unwrap();
+                cube.apply(&scramble).unwrap();
+                assert!(!cube.is_solved());
+            }
+        }
+
+        let scramble = MoveScrambler::from_seed(2, 1).unwrap().scramble();
+        assert!(scramble
+            .iter()
+            .all(|mv| !mv.axis.is_negative() && mv.affected_range == Layer::Single(0).into()));
+        let scramble = MoveScrambler::from_seed(4, 1).unwrap().scramble();
+        assert!(scramble
+            .iter()
+            .any(|mv| mv.affected_range == Layer::Multiple(2).into()));
+    }
+
+    #[test]
+    fn test_move_scrambler_seed() {
+        let scramble = |seed| {
+            MoveScrambler::from_seed(3, seed)
+                .unwrap()
+                .with_length(30)
+                .scramble()
+        };
+        assert_eq!(scramble(42), scramble(42));
+        assert_ne!(scramble(42), scramble(43));
+        assert_eq!(scramble(42).len(), 30);
+        assert_eq!(
+            MoveScrambler::from_seed(1, 0).unwrap_err(),
+            Error::InvalidNumberSides(1)
+        );
+    }
+
+    #[test]
+    fn test_state_scrambler() {
+        for sides in 2..=3 {
+            let mut scrambler = StateScrambler::from_seed(sides, 9).unwrap();
+            for _ in 0..10 {
+                let (scramble, state) = scrambler.scramble_with_state().unwrap();
+                assert!(scramble.len() <= STATE_SCRAMBLE_LENGTH);
+                let mut cube = Cube::with_number_sides(sides).unwrap();
+                cube.apply(&scramble).unwrap();
+                assert_eq!(cube.to_facelets(), state.to_facelets());
+            }
+        }
+
+        let scramble = StateScrambler::from_seed(2, 4).unwrap().scramble().unwrap();
+        assert!(scramble.len() <= 11);
+        assert!(scramble
+            .iter()
+            .all(|mv| !mv.axis.is_negative() && mv.affected_range == Layer::Single(0).into()));
+
+        let scramble = |seed| {
+            StateScrambler::from_seed(3, seed)
+                .unwrap()
+                .scramble()
+                .unwrap()
+        };
+        assert_eq!(scramble(42), scramble(42));
+        assert_ne!(scramble(42), scramble(43));
+        for sides in [1, 4, 5].iter() {
+            assert_eq!(
+                StateScrambler::from_seed(*sides, 0).unwrap_err(),
+                Error::UnsupportedRandomState(*sides)
+            );
+        }
+    }
+}
@@ -0,0 +1,310 @@
+use crate::{
+    cube::Cube,
+    facelet::{Color, Face, Facelets},
+};
+use nalgebra::{Vector2, Vector3};
+use std::fmt::Write;
+
+/// The picture drawn by [`Facelets::to_svg`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum SvgView {
+    /// Every side unfolded into a cross, like [`Facelets::to_net`].
+    #[default]
+    Net,
+    /// The up, front and right sides as seen from above the front right corner.
+    Isometric,
+}
+
+/// The colors used to draw each sticker.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ColorScheme {
+    colors: [[u8; 3]; 6],
+}
+
+impl ColorScheme {
+    /// The red, green and blue values used for a color.
+    pub fn rgb(&self, color: Color) -> [u8; 3] {
+        self.colors[color as usize]
+    }
+
+    /// Draw a color with other red, green and blue values.
+    pub fn set(&mut self, color: Color, rgb: [u8; 3]) {
+        self.colors[color as usize] = rgb;
+    }
+}
+
+impl Default for ColorScheme {
+    /// The values of [`Color::rgb`].
+    fn default() -> Self {
+        let mut colors = [[0; 3]; 6];
+        for face in Face::ALL.iter() {
+            colors[face.color() as usize] = face.color().rgb();
+        }
+        ColorScheme { colors }
+    }
+}
+
+/// How a cube is drawn as an SVG image.
+///
+/// Sizes are in pixels. Big cubes need small stickers, a 100x100x100 with
+/// 2 pixel stickers and no gap is still over 800 pixels wide. Without a gap,
+/// neighbouring stickers of the same color are drawn as one shape to keep the
+/// image small.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SvgOptions {
+    /// Whether the cube is drawn unfolded or as seen from a corner.
+    pub view: SvgView,
+    /// Length of the side of a sticker.
+    pub sticker_size: f64,
+    /// Space around each sticker, showing the plastic of the cube.
+    pub gap: f64,
+    /// The color of each sticker.
+    pub scheme: ColorScheme,
+    /// Sides drawn at full strength, the others are faded. Every side is drawn
+    /// at full strength when empty.
+    pub highlight: Vec<Face>,
+}
+
+impl Default for SvgOptions {
+    fn default() -> Self {
+        SvgOptions {
+            view: SvgView::Net,
+            sticker_size: 20.0,
+            gap: 2.0,
+            scheme: ColorScheme::default(),
+            highlight: Vec::new(),
+        }
+    }
+}
+
+/// Opacity of the sides that aren't highlighted.
+const FADED: f64 = 0.3;
+
+/// Where a side is drawn, as the image position of its top left corner and
+/// the image directions of its rows and columns.
+struct Placement {
+    face: Face,
+    origin: Vector2<f64>,
+    right: Vector2<f64>,
+    down: Vector2<f64>,
+}
+
+impl Facelets {
+    /// Draw the stickers as an SVG image.
+    pub fn to_svg(&self, options: &SvgOptions) -> String {
+        let size = self.sides() as f64 * (options.sticker_size + options.gap) + options.gap;
+        let placements = match options.view {
+            SvgView::Net => net_placements(size),
+            SvgView::Isometric => isometric_placements(size),
+        };
+        let corners = placements.iter().flat_map(|p| {
+            vec![
+                p.origin,
+                p.origin + p.right * size,
+                p.origin + p.down * size,
+                p.origin + (p.right + p.down) * size,
+            ]
+        });
+        let (min, max) = corners.fold(
+            (
+                Vector2::repeat(f64::INFINITY),
+                Vector2::repeat(f64::NEG_INFINITY),
+            ),
+            |(min, max), c| (min.inf(&c), max.sup(&c)),
+        );
+        let margin = options.gap.max(1.0);
+        let shift = Vector2::repeat(margin) - min;
+        let (width, height) = (max.x - min.x + 2.0 * margin, max.y - min.y + 2.0 * margin);
+
+        let mut svg = String::new();
+        write!(
+            svg,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w}" height="{h}" viewBox="0 0 {w} {h}">"#,
+            w = num(width),
+            h = num(height),
+        )
+        .expect("writing to a String can't fail");
+        for p in placements.iter() {
+            let origin = p.origin + shift;
+            let point = |u: f64, v: f64| origin + p.right * u + p.down * v;
+            let highlighted = options.highlight.is_empty() || options.highlight.contains(&p.face);
+            if highlighted {
+                svg.push_str("<g>");
+            } else {
+                svg.push_str(&format!(r#"<g opacity="{}">"#, num(FADED)));
+            }
+            svg.push_str(&polygon(
+                &[
+                    point(0.0, 0.0),
+                    point(size, 0.0),
+                    point(size, size),
+                    point(0.0, size),
+                ],
+                "#000000",
+            ));
+            let step = options.sticker_size + options.gap;
+            for row in 0..self.sides() {
+                let top = options.gap + row as f64 * step;
+                let mut col = 0;
+                while col < self.sides() {
+                    let color = self.get(p.face, row, col);
+                    let mut len = 1;
+                    if options.gap == 0.0 {
+                        while col + len < self.sides() && self.get(p.face, row, col + len) == color
+                        {
+                            len += 1;
+                        }
+                    }
+                    let left = options.gap + col as f64 * step;
+                    let right = left + len as f64 * step - options.gap;
+                    let bottom = top + options.sticker_size;
+                    svg.push_str(&polygon(
+                        &[
+                            point(left, top),
+                            point(right, top),
+                            point(right, bottom),
+                            point(left, bottom),
+                        ],
+                        &hex(options.scheme.rgb(color)),
+                    ));
+                    col += len;
+                }
+            }
+            svg.push_str("</g>");
+        }
+        svg.push_str("</svg>");
+        svg
+    }
+}
+
+impl Cube {
+    /// Draw the cube as an SVG image, see [`Facelets::to_svg`].
+    pub fn to_svg(&self, options: &SvgOptions) -> String {
+        self.to_facelets().to_svg(options)
+    }
+}
+
+/// Sides laid out in the same cross as [`Facelets::to_net`].
+fn net_placements(size: f64) -> Vec<Placement> {
+    let spacing = size / 20.0;
+    let at = |face, col: f64, row: f64| Placement {
+        face,
+        origin: Vector2::new(col, row) * (size + spacing),
+        right: Vector2::x(),
+        down: Vector2::y(),
+    };
+    vec![
+        at(Face::Up, 1.0, 0.0),
+        at(Face::Left, 0.0, 1.0),
+        at(Face::Front, 1.0, 1.0),
+        at(Face::Right, 2.0, 1.0),
+        at(Face::Back, 3.0, 1.0),
+        at(Face::Down, 1.0, 2.0),
+    ]
+}
+
+/// The up, front and right sides projected so the cube's edges are all the
+/// same length in the image.
+fn isometric_placements(size: f64) -> Vec<Placement> {
+    let (sin, cos) = std::f64::consts::FRAC_PI_6.sin_cos();
+    // Front (+x) is down to the left, right (+y) down to the right, up (+z) is up.
+    let project = |v: Vector3<isize>| {
+        let v = v.map(|c| c as f64);
+        Vector2::new(cos * (v.y - v.x), sin * (v.x + v.y) - v.z)
+    };
+    [Face::Up, Face::Front, Face::Right]
+        .iter()
+        .map(|&face| {
+            let corner = face.normal() - face.right() - face.down();
+            Placement {
+                face,
+                origin: project(corner) * (size / 2.0),
+                right: project(face.right()),
+                down: project(face.down()),
+            }
+        })
+        .collect()
+}
+
+fn polygon(points: &[Vector2<f64>], fill: &str) -> String {
+    let points: Vec<_> = points
+        .iter()
+        .map(|p| format!("{},{}", num(p.x), num(p.y)))
+        .collect();
+    format!(
+        r#"<polygon points="{}" fill="{}"/>"#,
+        points.join(" "),
+        fill
+    )
+}
+
+fn hex([r, g, b]: [u8; 3]) -> String {
+    format!("#{:02x}{:02x}{:02x}", r, g, b)
+}
+
+/// A number with at most two decimal places and no trailing zeros.
+fn num(v: f64) -> String {
+    let s = format!("{:.2}", v);
+    let s = s.trim_end_matches('0').trim_end_matches('.');
+    if s == "-0" {
+        "0".to_string()
+    } else {
+        s.to_string()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_net() {
+        let svg = Cube::new2x2x2().to_svg(&SvgOptions::default());
+        // 6 sides each with 4 stickers.
+        assert_eq!(svg.matches("<polygon").count(), 30);
+        assert_eq!(svg.matches(r##"fill="#ffd500""##).count(), 4);
+        assert!(svg.starts_with(
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="194.9" height="146.6""#
+        ));
+        assert!(svg.ends_with("</g></svg>"));
+    }
+
+    #[test]
+    fn test_isometric() {
+        let mut cube = Cube::new3x3x3();
+        cube.apply(&"R".parse().unwrap()).unwrap();
+        let mut scheme = ColorScheme::default();
+        scheme.set(Color::White, [128, 128, 128]);
+        let svg = cube.to_svg(&SvgOptions {
+            view: SvgView::Isometric,
+            scheme,
+            highlight: vec![Face::Front],
+            ..SvgOptions::default()
+        });
+        assert_eq!(svg.matches("<polygon").count(), 30);
+        // The front shows the white stickers moved up from the bottom.
+        assert_eq!(svg.matches(r##"fill="#808080""##).count(), 3);
+        assert_eq!(svg.matches(r#"<g opacity="0.3">"#).count(), 2);
+    }
+
+    #[test]
+    fn test_merged_stickers() {
+        let cube = Cube::with_number_sides(100).unwrap();
+        let svg = cube.to_svg(&SvgOptions {
+            sticker_size: 2.0,
+            gap: 0.0,
+            ..SvgOptions::default()
+        });
+        // Every row of a solved side is a single shape.
+        assert_eq!(svg.matches("<polygon").count(), 6 * 101);
+        assert!(svg.len() < 100_000);
+    }
+
+    #[test]
+    fn test_num() {
+        assert_eq!(num(1.0), "1");
+        assert_eq!(num(1.25), "1.25");
+        assert_eq!(num(1.5004), "1.5");
+        assert_eq!(num(-0.001), "0");
+    }
+}
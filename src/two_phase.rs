@@ -0,0 +1,348 @@
+use crate::{
+    coord::{
+        is_redundant, move_tables, prune_table, MoveTables, PHASE2_TURNS, SLICES, SLICE_EDGES,
+    },
+    cube::Cube,
+    cubie::{face_turn, CubieCube, FACE_TURNS},
+    error::Error,
+    movement::Move,
+};
+use std::{
+    sync::OnceLock,
+    time::{Duration, Instant},
+};
+
+/// Lower bounds on the turns left in each phase.
+struct PruneTables {
+    twist_slice: Vec<u8>,
+    flip_slice: Vec<u8>,
+    corners_slice_edges: Vec<u8>,
+    ud_edges_slice_edges: Vec<u8>,
+}
+
+fn prune_tables() -> &'static PruneTables {
+    static TABLES: OnceLock<PruneTables> = OnceLock::new();
+    TABLES.get_or_init(|| {
+        let moves = move_tables();
+        let all: Vec<usize> = (0..FACE_TURNS).collect();
+        PruneTables {
+            twist_slice: prune_table(&moves.twist, &moves.slice, &all),
+            flip_slice: prune_table(&moves.flip, &moves.slice, &all),
+            corners_slice_edges: prune_table(&moves.corners, &moves.slice_edges, &PHASE2_TURNS),
+            ud_edges_slice_edges: prune_table(&moves.ud_edges, &moves.slice_edges, &PHASE2_TURNS),
+        }
+    })
+}
+
+/// Turns allowed in the first solution found, enough to solve any cube.
+const FIRST_BOUND: usize = 30;
+
+/// The number of positions searched between checks of the time limit.
+const NODES_PER_CHECK: usize = 1 << 12;
+
+/// Finds short solutions of a 3x3x3 with Kociemba's two-phase algorithm.
+///
+/// The first phase turns the cube into the group reachable with `U`, `D`,
+/// `R2`, `L2`, `F2` and `B2`, where every piece is oriented and the middle
+/// layer edges are in the middle layer, and the second phase solves it with
+/// only those turns. Both phases are searched with tables of the fewest turns
+/// left, which are made the first time a cube is solved.
+///
+/// The first solution is found quickly and the search carries on looking for
+/// shorter ones, until one has at most [`TwoPhaseSolver::with_max_length`]
+/// turns or the time limit is reached. Solutions are made of face turns only.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TwoPhaseSolver {
+    max_length: usize,
+    timeout: Option<Duration>,
+}
+
+impl Default for TwoPhaseSolver {
+    /// Look for solutions of at most 21 turns without a time limit.
+    fn default() -> Self {
+        TwoPhaseSolver {
+            max_length: 21,
+            timeout: None,
+        }
+    }
+}
+
+impl TwoPhaseSolver {
+    /// Create a solver looking for solutions of at most 21 turns without a
+    /// time limit.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Stop searching once a solution with at most `max_length` turns is found.
+    ///
+    /// Every cube can be solved in 20 turns, but looking for solutions that
+    /// short takes hundreds of times longer than for 21 turns.
+    pub fn with_max_length(mut self, max_length: usize) -> Self {
+        self.max_length = max_length;
+        self
+    }
+
+    /// Give up searching for shorter solutions after some time, not counting
+    /// the time to make the tables.
+    ///
+    /// Solving fails with [`Error::SolveTimeout`] if no solution of at most
+    /// the maximum length was found in time, even if a longer one was.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Moves solving a 3x3x3 as it is held, see [`CubieCube::from_cube`].
+    pub fn solve(&self, cube: &Cube) -> Result<Vec<Move>, Error> {
+        self.solve_cubies(&CubieCube::from_cube(cube)?)
+    }
+
+    /// Moves solving a cubie cube.
+    ///
+    /// Returns [`Error::Unsolvable`] if the cube can't be solved by turning the
+    /// sides, [`Error::NoSolution`] if it can't be solved in the maximum length
+    /// and [`Error::SolveTimeout`] if no solution was found in time.
+    pub fn solve_cubies(&self, cube: &CubieCube) -> Result<Vec<Move>, Error> {
+        if !cube.is_solvable() {
+            return Err(Error::Unsolvable);
+        }
+        let mut search = Search {
+            moves: move_tables(),
+            prune: prune_tables(),
+            cube: *cube,
+            target: self.max_length,
+            bound: FIRST_BOUND.max(self.max_length),
+            deadline: self.timeout.map(|t| Instant::now() + t),
+            nodes: 0,
+            timed_out: false,
+            turns: Vec::new(),
+            best: None,
+        };
+        let (twist, flip, slice) = (cube.twist(), cube.flip(), cube.slice());
+        let mut depth = search.phase1_bound(twist, flip, slice);
+        while depth <= search.bound && !search.phase1(twist, flip, slice, depth) {
+            depth += 1;
+        }
+        match search.best {
+            Some(best) if best.len() <= self.max_length => {
+                Ok(best.into_iter().map(face_turn).collect())
+            }
+            _ if search.timed_out => Err(Error::SolveTimeout),
+            _ => Err(Error::NoSolution(self.max_length)),
+        }
+    }
+}
+
+struct Search {
+    moves: &'static MoveTables,
+    prune: &'static PruneTables,
+    cube: CubieCube,
+    /// Stop once a solution this short is found.
+    target: usize,
+    /// The most turns a solution may have to be better than the best so far.
+    bound: usize,
+    deadline: Option<Instant>,
+    nodes: usize,
+    timed_out: bool,
+    turns: Vec<usize>,
+    best: Option<Vec<usize>>,
+}
+
+impl Search {
+    fn phase1_bound(&self, twist: usize, flip: usize, slice: usize) -> usize {
+        self.prune.twist_slice[twist * SLICES + slice]
+            .max(self.prune.flip_slice[flip * SLICES + slice]) as usize
+    }
+
+    fn phase2_bound(&self, corners: usize, ud_edges: usize, slice_edges: usize) -> usize {
+        self.prune.corners_slice_edges[corners * SLICE_EDGES + slice_edges]
+            .max(self.prune.ud_edges_slice_edges[ud_edges * SLICE_EDGES + slice_edges])
+            as usize
+    }
+
+    /// Whether the search should stop, checking the time every so often.
+    fn is_done(&mut self) -> bool {
+        self.nodes += 1;
+        if self.nodes % NODES_PER_CHECK == 0 {
+            if let Some(deadline) = self.deadline {
+                self.timed_out = self.timed_out || Instant::now() >= deadline;
+            }
+        }
+        self.timed_out
+    }
+
+    /// Search first phases of exactly `togo` more turns, returning whether the
+    /// search is over.
+    fn phase1(&mut self, twist: usize, flip: usize, slice: usize, togo: usize) -> bool {
+        if self.is_done() {
+            return true;
+        }
+        if togo == 0 {
+            // A first phase ending in a second phase turn was already tried
+            // without that turn.
+            let last = self.turns.last();
+            return twist == 0
+                && flip == 0
+                && slice == 0
+                && last.map_or(true, |m| !PHASE2_TURNS.contains(m))
+                && self.start_phase2();
+        }
+        for m in 0..FACE_TURNS {
+            if is_redundant(self.turns.last().copied(), m) {
+                continue;
+            }
+            let twist = self.moves.twist[twist][m] as usize;
+            let flip = self.moves.flip[flip][m] as usize;
+            let slice = self.moves.slice[slice][m] as usize;
+            if self.phase1_bound(twist, flip, slice) >= togo {
+                continue;
+            }
+            self.turns.push(m);
+            let done = self.phase1(twist, flip, slice, togo - 1);
+            self.turns.pop();
+            if done {
+                return true;
+            }
+        }
+        false
+    }
+
+    fn start_phase2(&mut self) -> bool {
+        let cube = self.turns.iter().fold(self.cube, |c, &m| c.turn(m));
+        let (corners, ud_edges, slice_edges) =
+            (cube.corners(), cube.ud_edges(), cube.slice_edges());
+        let phase1 = self.turns.len();
+        let mut depth = self.phase2_bound(corners, ud_edges, slice_edges);
+        while phase1 + depth <= self.bound {
+            if self.phase2(corners, ud_edges, slice_edges, depth) {
+                break;
+            }
+            depth += 1;
+        }
+        self.timed_out || self.best.as_ref().is_some_and(|b| b.len() <= self.target)
+    }
+
+    /// Search second phases of exactly `togo` more turns, returning whether
+    /// one was found or the search is over.
+    fn phase2(&mut self, corners: usize, ud_edges: usize, slice_edges: usize, togo: usize) -> bool {
+        if self.is_done() {
+            return true;
+        }
+        if togo == 0 {
+            if corners == 0 && ud_edges == 0 && slice_edges == 0 {
+                self.bound = self.turns.len().saturating_sub(1);
+                self.best = Some(self.turns.clone());
+                return true;
+            }
+            return false;
+        }
+        for &m in PHASE2_TURNS.iter() {
+            if is_redundant(self.turns.last().copied(), m) {
+                continue;
+            }
+            let corners = self.moves.corners[corners][m] as usize;
+            let ud_edges = self.moves.ud_edges[ud_edges][m] as usize;
+            let slice_edges = self.moves.slice_edges[slice_edges][m] as usize;
+            if self.phase2_bound(corners, ud_edges, slice_edges) >= togo {
+                continue;
+            }
+            self.turns.push(m);
+            let found = self.phase2(corners, ud_edges, slice_edges, togo - 1);
+            self.turns.pop();
+            if found {
+                return true;
+            }
+        }
+        false
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        algorithm::Algorithm,
+        movement::{Layer, MoveType},
+    };
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    #[test]
+    fn test_solve() {
+        let solver = TwoPhaseSolver::new();
+        assert_eq!(solver.solve(&Cube::new3x3x3()), Ok(vec![]));
+        let mut cube = Cube::new3x3x3();
+        cube.apply(&"x R' y".parse().unwrap()).unwrap();
+        assert_eq!(
+            solver.solve(&cube),
+            Ok(vec![Move::rotate_front(
+                Layer::Single(0),
+                MoveType::Clockwise
+            )])
+        );
+
+        let mut rng = ChaCha8Rng::seed_from_u64(11);
+        for _ in 0..5 {
+            let mut cube = CubieCube::random(&mut rng).to_cube();
+            let solution = solver.solve(&cube).unwrap();
+            assert!(solution.len() <= 21);
+            cube.apply(&Algorithm::from(solution)).unwrap();
+            assert!(cube.is_solved());
+        }
+    }
+
+    #[test]
+    fn test_solve_limits() {
+        let mut cube = Cube::new3x3x3();
+        cube.apply(&"R U F D2 L' B".parse().unwrap()).unwrap();
+        assert_eq!(
+            TwoPhaseSolver::new().with_max_length(5).solve(&cube),
+            Err(Error::NoSolution(5))
+        );
+        assert_eq!(
+            TwoPhaseSolver::new()
+                .with_max_length(6)
+                .solve(&cube)
+                .unwrap()
+                .len(),
+            6
+        );
+
+        // Running out of time fails rather than give a longer solution. The
+        // solves above have already built the tables.
+        let state = CubieCube::random(&mut ChaCha8Rng::seed_from_u64(3));
+        let timeout = Duration::from_millis(100);
+        let start = Instant::now();
+        assert_eq!(
+            TwoPhaseSolver::new()
+                .with_max_length(0)
+                .with_timeout(timeout)
+                .solve_cubies(&state),
+            Err(Error::SolveTimeout)
+        );
+        assert!(start.elapsed() >= timeout);
+        let solution = TwoPhaseSolver::new()
+            .with_timeout(Duration::from_secs(60))
+            .solve_cubies(&state)
+            .unwrap();
+        assert!(solution.len() <= 21);
+        let mut cube = state.to_cube();
+        cube.apply(&Algorithm::from(solution)).unwrap();
+        assert!(cube.is_solved());
+
+        let mut twisted = CubieCube::SOLVED;
+        twisted.co[0] = 1;
+        assert_eq!(
+            TwoPhaseSolver::new().solve(&twisted.to_cube()),
+            Err(Error::Unsolvable)
+        );
+        assert_eq!(
+            TwoPhaseSolver::new().solve(&Cube::new2x2x2()),
+            Err(Error::UnsupportedSides {
+                expected: 3,
+                found: 2
+            })
+        );
+    }
+}